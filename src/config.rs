@@ -0,0 +1,189 @@
+use std::{collections::BTreeMap, env, fs, io, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::keymap::{Action, KeyBinding, KeyContext, Keymap};
+
+/// Settings read from `$XDG_CONFIG_HOME/trowel/config.toml`, such as:
+///
+/// ```toml
+/// [keys.anywhere]
+/// quit = ["ctrl-q"]
+///
+/// [keys.tree]
+/// down = ["down", "ctrl-n"]
+/// up = ["up", "ctrl-p"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub keys: KeysConfig,
+}
+
+/// The keys to bind to actions in each view, replacing the keys they're bound to by default
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeysConfig {
+    #[serde(default)]
+    pub anywhere: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub tree: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub text: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub workspaces: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub planning: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub diagnostics: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub typing: BTreeMap<Action, Vec<KeyBinding>>,
+    #[serde(default)]
+    pub help: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Config {
+    /// Reads the config file, if there is one
+    pub fn load() -> Result<Self, io::Error> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid config file {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Where the config file is, following the XDG base directory specification
+    pub fn path() -> Option<PathBuf> {
+        let dir = match env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(dir.join("trowel").join("config.toml"))
+    }
+
+    /// The default keys, with those in the config file bound instead
+    pub fn keymap(&self) -> Result<Keymap, io::Error> {
+        let mut keymap = Keymap::default();
+        let sections = [
+            ("anywhere", KeyContext::Anywhere, &self.keys.anywhere),
+            ("tree", KeyContext::Tree, &self.keys.tree),
+            ("text", KeyContext::Text, &self.keys.text),
+            ("workspaces", KeyContext::Workspaces, &self.keys.workspaces),
+            ("planning", KeyContext::Planning, &self.keys.planning),
+            (
+                "diagnostics",
+                KeyContext::Diagnostics,
+                &self.keys.diagnostics,
+            ),
+            ("typing", KeyContext::Typing, &self.keys.typing),
+            ("help", KeyContext::Help, &self.keys.help),
+        ];
+        for (section, context, keys) in sections {
+            keymap.rebind(context, keys).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid config file: [keys.{}] {}", section, e),
+                )
+            })?;
+        }
+        Ok(keymap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::state::planning_view_state::{PlanningStatus, PlanningViewState};
+
+    use super::*;
+
+    #[test]
+    fn test_keymap() {
+        let config: Config = toml::from_str(
+            r#"
+            [keys.tree]
+            down = ["ctrl-n", "down"]
+            search = ["s"]
+            toggle_detail = []
+            "#,
+        )
+        .unwrap();
+        let keymap = config.keymap().unwrap();
+        let action =
+            |code, modifiers| keymap.action(KeyContext::Tree, &KeyEvent::new(code, modifiers));
+        assert_eq!(
+            action(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            Some(Action::Down)
+        );
+        assert_eq!(action(KeyCode::Char('j'), KeyModifiers::NONE), None);
+        assert_eq!(
+            action(KeyCode::Char('s'), KeyModifiers::NONE),
+            Some(Action::Search)
+        );
+        assert_eq!(action(KeyCode::Char('p'), KeyModifiers::NONE), None);
+        // Other views keep their keys
+        assert_eq!(
+            keymap.action(
+                KeyContext::Text,
+                &KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)
+            ),
+            Some(Action::Down)
+        );
+    }
+
+    #[test]
+    fn test_keymap_quits_while_planning() {
+        let config: Config = toml::from_str("[keys.anywhere]\nquit = [\"ctrl-q\"]").unwrap();
+        let keymap = config.keymap().unwrap();
+        let (mut state, _tx, _interrupt_rx) = PlanningViewState::new(None);
+        let press = |code, modifiers| Event::Key(KeyEvent::new(code, modifiers));
+
+        state.process_event(press(KeyCode::Char('q'), KeyModifiers::NONE), &keymap);
+        assert!(matches!(state.status, PlanningStatus::Running));
+        state.process_event(press(KeyCode::Char('q'), KeyModifiers::CONTROL), &keymap);
+        assert!(matches!(state.status, PlanningStatus::Interrupting));
+    }
+
+    #[test]
+    fn test_keymap_moves_keys() {
+        // `n` is taken from the next match, which is left with no keys
+        let config: Config = toml::from_str("[keys.text]\nnext_resource = [\"n\"]").unwrap();
+        let keymap = config.keymap().unwrap();
+        let key = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(
+            keymap.action(KeyContext::Text, &key),
+            Some(Action::NextResource)
+        );
+        assert!(
+            !keymap
+                .help(KeyContext::Text, true)
+                .iter()
+                .any(|(_, d)| *d == "Next match")
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(toml::from_str::<Config>("[keys.tree]\nobliterate = [\"o\"]").is_err());
+        assert!(toml::from_str::<Config>("[keys.tree]\ndown = [\"hyper-j\"]").is_err());
+        assert!(toml::from_str::<Config>("[keys.tree]\ndown = [\"pagedown\"]").is_err());
+        assert!(toml::from_str::<Config>("[colours]\ntree = \"red\"").is_err());
+
+        let config: Config = toml::from_str("[keys.tree]\nreplan = [\"r\"]").unwrap();
+        assert_eq!(
+            config.keymap().unwrap_err().to_string(),
+            "Invalid config file: [keys.tree] \"replan\" isn't an action in this view"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::{io, path::Path, time::Duration};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::{sync::mpsc, time::Instant};
+
+/// Watches a working directory for changes to files that affect a plan
+pub struct ConfigWatcher {
+    // Watching stops when this is dropped
+    _watcher: RecommendedWatcher,
+    rx: mpsc::Receiver<()>,
+    /// When the most recent burst of changes is considered finished
+    deadline: Option<Instant>,
+}
+
+impl ConfigWatcher {
+    /// Editors often write a file several times when saving, so changes are only reported
+    /// once none have happened for this long
+    const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn new(dir: &Path) -> Result<Self, io::Error> {
+        // Only the fact that something changed matters, so a full channel can drop changes
+        let (tx, rx) = mpsc::channel(1);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && is_config_event(&event)
+                {
+                    tx.try_send(()).ok();
+                }
+            })
+            .map_err(io::Error::other)?;
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .map_err(io::Error::other)?;
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            deadline: None,
+        })
+    }
+
+    /// Resolves once the configuration has changed and then settled. This is cancel safe, so
+    /// it can be polled in a loop alongside other events without losing changes.
+    pub async fn settled(&mut self) {
+        loop {
+            match self.deadline {
+                None => match self.rx.recv().await {
+                    Some(()) => self.deadline = Some(Instant::now() + Self::DEBOUNCE_INTERVAL),
+                    None => std::future::pending().await,
+                },
+                Some(deadline) => tokio::select! {
+                    Some(()) = self.rx.recv() => {
+                        self.deadline = Some(Instant::now() + Self::DEBOUNCE_INTERVAL);
+                    }
+                    _ = tokio::time::sleep_until(deadline) => {
+                        self.deadline = None;
+                        return;
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn is_config_event(event: &notify::Event) -> bool {
+    let modifies = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    modifies && event.paths.iter().any(|p| is_config_file(p))
+}
+
+/// Whether a file is configuration, variables or the dependency lock file. Anything under
+/// `.terraform` is ignored, since init writes downloaded modules there.
+fn is_config_file(path: &Path) -> bool {
+    const EXTENSIONS: [&str; 4] = [".tf", ".tfvars", ".tf.json", ".tfvars.json"];
+
+    if path.components().any(|c| c.as_os_str() == ".terraform") {
+        return false;
+    }
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    name == ".terraform.lock.hcl" || EXTENSIONS.iter().any(|e| name.ends_with(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_config_file() {
+        assert!(is_config_file(Path::new("/work/main.tf")));
+        assert!(is_config_file(Path::new("/work/prod.tfvars")));
+        assert!(is_config_file(Path::new("/work/override.tf.json")));
+        assert!(is_config_file(Path::new("/work/modules/fruit/main.tf")));
+        assert!(is_config_file(Path::new("/work/.terraform.lock.hcl")));
+        assert!(!is_config_file(Path::new("/work/README.md")));
+        assert!(!is_config_file(Path::new("/work/main.tf.swp")));
+        assert!(!is_config_file(Path::new(
+            "/work/.terraform/modules/fruit/main.tf"
+        )));
+    }
+}
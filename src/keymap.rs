@@ -0,0 +1,724 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::model::verb::Verb;
+
+/// Something that a key can be bound to do, named in snake case in the config file, e.g.
+/// `next_match`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    // Anywhere
+    Quit,
+    ToggleView,
+    ToggleWorkspaces,
+    ToggleHelp,
+
+    // Moving around
+    Up,
+    Down,
+    Left,
+    Right,
+    UpMany,
+    DownMany,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+
+    // Folding
+    ToggleFold,
+    OpenAll,
+    CloseAll,
+    OpenToDepth,
+
+    // Searching and filtering
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ClearSearch,
+    Filter,
+    ShowAllActions,
+    ToggleCreate,
+    ToggleDestroy,
+    ToggleReplace,
+    ToggleUpdate,
+    ToggleRead,
+    ToggleForget,
+
+    // Typing a search or filter
+    FinishTyping,
+    CancelTyping,
+
+    // Details
+    ToggleDetail,
+    ScrollDetailUp,
+    ScrollDetailDown,
+
+    // Text plan
+    NextResource,
+    PreviousResource,
+
+    // Workspaces
+    DisplayWorkspace,
+    Replan,
+    PlanAll,
+
+    // Planning
+    RunInit,
+}
+
+impl Action {
+    /// The name used in the config file, e.g. `next_match`
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        for c in format!("{:?}", self).chars() {
+            if c.is_ascii_uppercase() && !name.is_empty() {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Quit => "Quit",
+            Self::ToggleView => "Switch between the tree and the text plan",
+            Self::ToggleWorkspaces => "Show or hide the workspaces",
+            Self::ToggleHelp => "Show or hide this help",
+            Self::Up => "Move up",
+            Self::Down => "Move down",
+            Self::Left => "Fold, or move to the parent",
+            Self::Right => "Unfold",
+            Self::UpMany => "Move up 10 lines",
+            Self::DownMany => "Move down 10 lines",
+            Self::PageUp => "Move up a page",
+            Self::PageDown => "Move down a page",
+            Self::Top => "Go to the top",
+            Self::Bottom => "Go to the bottom",
+            Self::ToggleFold => "Fold or unfold",
+            Self::OpenAll => "Unfold everything",
+            Self::CloseAll => "Fold everything",
+            Self::OpenToDepth => "Unfold to the depth typed next",
+            Self::Search => "Search",
+            Self::NextMatch => "Next match",
+            Self::PreviousMatch => "Previous match",
+            Self::ClearSearch => "Clear the search",
+            Self::Filter => "Filter by an expression",
+            Self::ShowAllActions => "Show every action",
+            Self::ToggleCreate => "Show or hide resources being created",
+            Self::ToggleDestroy => "Show or hide resources being destroyed",
+            Self::ToggleReplace => "Show or hide resources being replaced",
+            Self::ToggleUpdate => "Show or hide resources being updated",
+            Self::ToggleRead => "Show or hide data sources being read",
+            Self::ToggleForget => "Show or hide resources being forgotten",
+            Self::FinishTyping => "Finish typing a search or filter",
+            Self::CancelTyping => "Cancel typing a search or filter",
+            Self::ToggleDetail => "Show or hide the details",
+            Self::ScrollDetailUp => "Scroll the details up",
+            Self::ScrollDetailDown => "Scroll the details down",
+            Self::NextResource => "Next resource",
+            Self::PreviousResource => "Previous resource",
+            Self::DisplayWorkspace => "Display the workspace, planning it if needed",
+            Self::Replan => "Plan the workspace again",
+            Self::PlanAll => "Plan every unplanned workspace",
+            Self::RunInit => "Run init when the working directory isn't initialized",
+        }
+    }
+
+    /// The action that shows or hides resources with a verb in the tree
+    pub fn toggle_verb(verb: &Verb) -> Option<Self> {
+        match verb {
+            Verb::Create => Some(Self::ToggleCreate),
+            Verb::Destroy => Some(Self::ToggleDestroy),
+            Verb::Replace => Some(Self::ToggleReplace),
+            Verb::Update => Some(Self::ToggleUpdate),
+            Verb::Read => Some(Self::ToggleRead),
+            Verb::Forget => Some(Self::ToggleForget),
+            Verb::Ignore => None,
+        }
+    }
+
+    /// The verb this action shows or hides, if it's one of those from `toggle_verb`
+    pub fn toggled_verb(&self) -> Option<Verb> {
+        match self {
+            Self::ToggleCreate => Some(Verb::Create),
+            Self::ToggleDestroy => Some(Verb::Destroy),
+            Self::ToggleReplace => Some(Verb::Replace),
+            Self::ToggleUpdate => Some(Verb::Update),
+            Self::ToggleRead => Some(Verb::Read),
+            Self::ToggleForget => Some(Verb::Forget),
+            _ => None,
+        }
+    }
+}
+
+/// Where a key is pressed. Keys bound anywhere take precedence over those bound in a view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyContext {
+    Anywhere,
+    Tree,
+    Text,
+    Workspaces,
+    /// While planning, or offering to run init first
+    Planning,
+    /// Listing why a plan failed
+    Diagnostics,
+    /// While a search or filter is being typed, when other keys are typed as text
+    Typing,
+    /// While the help is shown
+    Help,
+}
+
+impl KeyContext {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Anywhere => "Anywhere",
+            Self::Tree => "Tree",
+            Self::Text => "Text plan",
+            Self::Workspaces => "Workspaces",
+            Self::Planning => "Planning",
+            Self::Diagnostics => "Diagnostics",
+            Self::Typing => "Typing",
+            Self::Help => "Help",
+        }
+    }
+}
+
+/// A key, along with any modifiers other than shift, which is part of the character typed.
+/// Written as in the help, e.g. `G`, `ctrl-d` or `pgdn`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c))
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        key.code == self.code
+            && key.modifiers.difference(KeyModifiers::SHIFT)
+                == self.modifiers.difference(KeyModifiers::SHIFT)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::PageUp => write!(f, "pgup"),
+            KeyCode::PageDown => write!(f, "pgdn"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = text;
+        // A lone `-` is a key rather than a separator
+        while let Some((modifier, rest)) = key.split_once('-')
+            && !rest.is_empty()
+        {
+            modifiers |= match modifier {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => return Err(format!("Unknown modifier \"{}\" in \"{}\"", modifier, text)),
+            };
+            key = rest;
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pgup" => KeyCode::PageUp,
+                "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                _ => match key.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) => KeyCode::F(n),
+                    None => return Err(format!("Unknown key \"{}\"", text)),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+/// The keys bound to an action in one context
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub context: KeyContext,
+    pub action: Action,
+    pub keys: Vec<KeyBinding>,
+}
+
+/// Which action each key performs, which is both how keys are dispatched and how they're
+/// described in the help
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    /// The action a key performs in a context, if any
+    pub fn action(&self, context: KeyContext, key: &KeyEvent) -> Option<Action> {
+        self.bindings(context)
+            .find(|b| b.keys.iter().any(|k| k.matches(key)))
+            .map(|b| b.action)
+    }
+
+    /// A reminder of the first key bound to each of some actions, e.g. `J/K: scroll | p: hide`,
+    /// leaving out actions with no keys
+    pub fn hint(&self, context: KeyContext, items: &[(&[Action], &str)]) -> String {
+        let parts: Vec<_> = items
+            .iter()
+            .filter_map(|(actions, label)| {
+                let keys: Option<Vec<_>> = actions
+                    .iter()
+                    .map(|a| self.first_key(context, *a))
+                    .collect();
+                Some(format!("{}: {}", keys?.join("/"), label))
+            })
+            .collect();
+        parts.join(" | ")
+    }
+
+    /// Some text followed by a hint, e.g. `match 1 of 3 | n/N: next/previous`
+    pub fn with_hint(
+        &self,
+        text: &str,
+        context: KeyContext,
+        items: &[(&[Action], &str)],
+    ) -> String {
+        let hint = self.hint(context, items);
+        let parts: Vec<_> = [text, &hint]
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .collect();
+        parts.join(" | ")
+    }
+
+    /// The first key bound to an action in a context, or anywhere, as it's written in the help
+    pub fn first_key(&self, context: KeyContext, action: Action) -> Option<String> {
+        self.bindings(context)
+            .chain(self.bindings(KeyContext::Anywhere))
+            .find(|b| b.action == action)
+            .and_then(|b| b.keys.first())
+            .map(|k| k.to_string())
+    }
+
+    /// Whether the key quits, which it does anywhere
+    pub fn is_quit(&self, key: &KeyEvent) -> bool {
+        self.action(KeyContext::Anywhere, key) == Some(Action::Quit)
+    }
+
+    /// Whether the key quits while a search or filter is being typed, which only keys that
+    /// wouldn't be typed as text do
+    pub fn is_quit_while_typing(&self, key: &KeyEvent) -> bool {
+        let is_text = matches!(key.code, KeyCode::Char(_))
+            && key.modifiers.difference(KeyModifiers::SHIFT).is_empty();
+        !is_text && self.is_quit(key)
+    }
+
+    /// Binds keys to actions in a context instead of the keys they were bound to. The keys are
+    /// unbound from any other action in the context, and an empty list leaves an action unbound.
+    pub fn rebind(
+        &mut self,
+        context: KeyContext,
+        keys: &BTreeMap<Action, Vec<KeyBinding>>,
+    ) -> Result<(), String> {
+        for (action, keys) in keys {
+            let Some(i) = self
+                .bindings
+                .iter()
+                .position(|b| b.context == context && b.action == *action)
+            else {
+                return Err(format!(
+                    "\"{}\" isn't an action in this view",
+                    action.name()
+                ));
+            };
+            for binding in self.bindings.iter_mut().filter(|b| b.context == context) {
+                binding.keys.retain(|k| !keys.contains(k));
+            }
+            if let Some(binding) = self.bindings.get_mut(i) {
+                binding.keys = keys.clone();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn bindings(&self, context: KeyContext) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter().filter(move |b| b.context == context)
+    }
+
+    /// Every binding that applies in a view, as `(keys, description)`, for the help. Views with a
+    /// search include the keys used while typing it. The keys for switching views are left out
+    /// of views that can't be switched from, such as those shown while planning.
+    pub fn help(&self, context: KeyContext, switches_views: bool) -> Vec<(String, &'static str)> {
+        let anywhere: Vec<_> = self
+            .bindings(KeyContext::Anywhere)
+            .flat_map(|b| &b.keys)
+            .collect();
+        let typing = match context {
+            KeyContext::Tree | KeyContext::Text => Some(KeyContext::Typing),
+            _ => None,
+        };
+        self.bindings(KeyContext::Anywhere)
+            .filter(|b| {
+                switches_views || !matches!(b.action, Action::ToggleView | Action::ToggleWorkspaces)
+            })
+            .chain(self.bindings(context))
+            .chain(typing.into_iter().flat_map(|c| self.bindings(c)))
+            .filter_map(|b| {
+                // Keys bound anywhere don't do anything else in a view, though they're typed
+                // as text in a search
+                let keys: Vec<_> = b
+                    .keys
+                    .iter()
+                    .filter(|k| {
+                        matches!(b.context, KeyContext::Anywhere | KeyContext::Typing)
+                            || !anywhere.contains(k)
+                    })
+                    .map(|k| k.to_string())
+                    .collect();
+                (!keys.is_empty()).then(|| (keys.join(", "), b.action.description()))
+            })
+            .collect()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode as K;
+        use KeyContext::*;
+        let char = KeyBinding::char;
+        let key = KeyBinding::new;
+
+        let table = [
+            (
+                Anywhere,
+                Action::Quit,
+                vec![char('q'), KeyBinding::ctrl('c')],
+            ),
+            (Anywhere, Action::ToggleView, vec![key(K::Tab)]),
+            (Anywhere, Action::ToggleWorkspaces, vec![char('w')]),
+            (Anywhere, Action::ToggleHelp, vec![char('?')]),
+            (Tree, Action::Up, vec![char('k'), key(K::Up)]),
+            (Tree, Action::Down, vec![char('j'), key(K::Down)]),
+            (Tree, Action::Left, vec![char('h'), key(K::Left)]),
+            (Tree, Action::Right, vec![char('l'), key(K::Right)]),
+            (Tree, Action::Top, vec![char('g'), key(K::Home)]),
+            (Tree, Action::Bottom, vec![char('G'), key(K::End)]),
+            (Tree, Action::ToggleFold, vec![key(K::Enter)]),
+            (Tree, Action::OpenAll, vec![char('E')]),
+            (Tree, Action::CloseAll, vec![char('C')]),
+            (Tree, Action::OpenToDepth, vec![char('z')]),
+            (Tree, Action::Search, vec![char('/')]),
+            (Tree, Action::NextMatch, vec![char('n')]),
+            (Tree, Action::PreviousMatch, vec![char('N')]),
+            (Tree, Action::ClearSearch, vec![key(K::Esc)]),
+            (Tree, Action::Filter, vec![char('f')]),
+            (Tree, Action::ShowAllActions, vec![char('0')]),
+            (Tree, Action::ToggleCreate, vec![char('1')]),
+            (Tree, Action::ToggleDestroy, vec![char('2')]),
+            (Tree, Action::ToggleReplace, vec![char('3')]),
+            (Tree, Action::ToggleUpdate, vec![char('4')]),
+            (Tree, Action::ToggleRead, vec![char('5')]),
+            (Tree, Action::ToggleForget, vec![char('6')]),
+            (Tree, Action::ToggleDetail, vec![char('p')]),
+            (Tree, Action::ScrollDetailUp, vec![char('K')]),
+            (Tree, Action::ScrollDetailDown, vec![char('J')]),
+            (Text, Action::Up, vec![char('k'), key(K::Up)]),
+            (Text, Action::Down, vec![char('j'), key(K::Down)]),
+            (Text, Action::UpMany, vec![char('u')]),
+            (Text, Action::DownMany, vec![char('d')]),
+            (Text, Action::PageUp, vec![key(K::PageUp)]),
+            (Text, Action::PageDown, vec![key(K::PageDown)]),
+            (Text, Action::Top, vec![char('g'), key(K::Home)]),
+            (Text, Action::Bottom, vec![char('G'), key(K::End)]),
+            (Text, Action::PreviousResource, vec![char('[')]),
+            (Text, Action::NextResource, vec![char(']')]),
+            (Text, Action::Search, vec![char('/')]),
+            (Text, Action::NextMatch, vec![char('n')]),
+            (Text, Action::PreviousMatch, vec![char('N')]),
+            (Text, Action::ClearSearch, vec![key(K::Esc)]),
+            (Workspaces, Action::Up, vec![char('k'), key(K::Up)]),
+            (Workspaces, Action::Down, vec![char('j'), key(K::Down)]),
+            (Workspaces, Action::DisplayWorkspace, vec![key(K::Enter)]),
+            (Workspaces, Action::Replan, vec![char('p')]),
+            (Workspaces, Action::PlanAll, vec![char('a')]),
+            (Planning, Action::RunInit, vec![char('i')]),
+            (Diagnostics, Action::Up, vec![char('k'), key(K::Up)]),
+            (Diagnostics, Action::Down, vec![char('j'), key(K::Down)]),
+            (Diagnostics, Action::Top, vec![char('g'), key(K::Home)]),
+            (Diagnostics, Action::Bottom, vec![char('G'), key(K::End)]),
+            (
+                Diagnostics,
+                Action::ScrollDetailUp,
+                vec![char('K'), key(K::PageUp)],
+            ),
+            (
+                Diagnostics,
+                Action::ScrollDetailDown,
+                vec![char('J'), key(K::PageDown)],
+            ),
+            (Typing, Action::FinishTyping, vec![key(K::Enter)]),
+            (Typing, Action::CancelTyping, vec![key(K::Esc)]),
+            (Help, Action::ToggleHelp, vec![key(K::Esc)]),
+            (Help, Action::Up, vec![char('k'), key(K::Up)]),
+            (Help, Action::Down, vec![char('j'), key(K::Down)]),
+            (Help, Action::PageUp, vec![key(K::PageUp)]),
+            (Help, Action::PageDown, vec![key(K::PageDown)]),
+            (Help, Action::Top, vec![char('g'), key(K::Home)]),
+            (Help, Action::Bottom, vec![char('G'), key(K::End)]),
+        ];
+        Self {
+            bindings: table
+                .into_iter()
+                .map(|(context, action, keys)| Binding {
+                    context,
+                    action,
+                    keys,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::trowel_filter::TrowelFilter;
+
+    use super::*;
+
+    #[test]
+    fn test_action() {
+        let keymap = Keymap::default();
+        let press = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(
+            keymap.action(
+                KeyContext::Tree,
+                &press(KeyCode::Char('p'), KeyModifiers::NONE)
+            ),
+            Some(Action::ToggleDetail)
+        );
+        assert_eq!(
+            keymap.action(
+                KeyContext::Workspaces,
+                &press(KeyCode::Char('p'), KeyModifiers::NONE)
+            ),
+            Some(Action::Replan)
+        );
+        // Shift is part of the character, but other modifiers have to match
+        assert_eq!(
+            keymap.action(
+                KeyContext::Tree,
+                &press(KeyCode::Char('G'), KeyModifiers::SHIFT)
+            ),
+            Some(Action::Bottom)
+        );
+        assert_eq!(
+            keymap.action(
+                KeyContext::Anywhere,
+                &press(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(
+                KeyContext::Tree,
+                &press(KeyCode::Char('j'), KeyModifiers::ALT)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_quit_while_typing() {
+        let mut keymap = Keymap::default();
+        let press = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert!(keymap.is_quit_while_typing(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!keymap.is_quit_while_typing(&press(KeyCode::Char('q'), KeyModifiers::NONE)));
+
+        keymap
+            .rebind(
+                KeyContext::Anywhere,
+                &BTreeMap::from([(Action::Quit, vec![KeyBinding::ctrl('q')])]),
+            )
+            .unwrap();
+        assert!(keymap.is_quit_while_typing(&press(KeyCode::Char('q'), KeyModifiers::CONTROL)));
+        assert!(!keymap.is_quit_while_typing(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        let parse = |text: &str| text.parse::<KeyBinding>();
+        assert_eq!(parse("j"), Ok(KeyBinding::char('j')));
+        assert_eq!(parse("-"), Ok(KeyBinding::char('-')));
+        assert_eq!(parse("ctrl-c"), Ok(KeyBinding::ctrl('c')));
+        assert_eq!(parse("pgdn"), Ok(KeyBinding::new(KeyCode::PageDown)));
+        assert_eq!(parse("f5"), Ok(KeyBinding::new(KeyCode::F(5))));
+        assert_eq!(
+            parse("ctrl-alt-space"),
+            Ok(KeyBinding {
+                code: KeyCode::Char(' '),
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            })
+        );
+        assert!(parse("hyper-j").is_err());
+        assert!(parse("pagedown").is_err());
+        assert!(parse("").is_err());
+
+        // Every key is written as it's parsed
+        for binding in Keymap::default().bindings.iter().flat_map(|b| &b.keys) {
+            assert_eq!(parse(&binding.to_string()), Ok(*binding));
+        }
+    }
+
+    #[test]
+    fn test_toggle_verb() {
+        for verb in &TrowelFilter::VERBS {
+            let action = Action::toggle_verb(verb).unwrap();
+            assert_eq!(action.toggled_verb().as_ref(), Some(verb));
+        }
+        assert_eq!(Action::toggle_verb(&Verb::Ignore), None);
+        assert_eq!(Action::ShowAllActions.toggled_verb(), None);
+    }
+
+    #[test]
+    fn test_action_name() {
+        assert_eq!(Action::Quit.name(), "quit");
+        assert_eq!(Action::ScrollDetailDown.name(), "scroll_detail_down");
+    }
+
+    #[test]
+    fn test_hint() {
+        let mut keymap = Keymap::default();
+        let items: &[(&[Action], &str)] = &[
+            (
+                &[Action::ScrollDetailDown, Action::ScrollDetailUp],
+                "scroll",
+            ),
+            (&[Action::ToggleDetail], "hide"),
+            (&[Action::ToggleWorkspaces], "workspaces"),
+        ];
+        assert_eq!(
+            keymap.hint(KeyContext::Tree, items),
+            "J/K: scroll | p: hide | w: workspaces"
+        );
+        keymap
+            .rebind(
+                KeyContext::Tree,
+                &BTreeMap::from([(Action::ToggleDetail, Vec::new())]),
+            )
+            .unwrap();
+        assert_eq!(
+            keymap.hint(KeyContext::Tree, items),
+            "J/K: scroll | w: workspaces"
+        );
+    }
+
+    #[test]
+    fn test_help() {
+        let help = Keymap::default().help(KeyContext::Text, true);
+        assert_eq!(help[0], ("q, ctrl-c".to_string(), "Quit"));
+        assert!(help.contains(&("pgdn".to_string(), "Move down a page")));
+        // Keys bound in other views aren't described
+        assert!(!help.iter().any(|(_, d)| *d == "Show or hide the details"));
+        assert!(help.contains(&("esc".to_string(), "Cancel typing a search or filter")));
+        let help = Keymap::default().help(KeyContext::Tree, true);
+        assert!(help.contains(&("1".to_string(), "Show or hide resources being created")));
+        assert!(help.contains(&("6".to_string(), "Show or hide resources being forgotten")));
+        // Views shown while planning can't be switched from
+        let help = Keymap::default().help(KeyContext::Planning, false);
+        assert_eq!(
+            help,
+            vec![
+                ("q, ctrl-c".to_string(), "Quit"),
+                ("?".to_string(), "Show or hide this help"),
+                (
+                    "i".to_string(),
+                    "Run init when the working directory isn't initialized"
+                ),
+            ]
+        );
+
+        let mut keymap = Keymap::default();
+        keymap
+            .rebind(
+                KeyContext::Anywhere,
+                &BTreeMap::from([(Action::ToggleHelp, vec![KeyBinding::char('h')])]),
+            )
+            .unwrap();
+        let help = keymap.help(KeyContext::Tree, true);
+        assert!(help.contains(&("left".to_string(), "Fold, or move to the parent")));
+        assert!(help.contains(&("h".to_string(), "Show or hide this help")));
+    }
+}
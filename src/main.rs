@@ -1,36 +1,82 @@
 use std::{
+    env,
     error::Error,
     ffi::OsStr,
     fs, io,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use clap::{Parser, command};
+use clap::{Parser, Subcommand, ValueEnum};
+use config::Config;
+use config_watcher::ConfigWatcher;
 use model::trowel_diff::TrowelDiff;
-use ratatui::{Frame, Terminal, backend::Backend, crossterm::event};
+use plan_output::PlanOutput;
+use policy::Policy;
+use ratatui::{
+    DefaultTerminal, Frame, Terminal,
+    backend::Backend,
+    crossterm::{
+        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
+        execute,
+    },
+};
 use state::{
     app_state::{AppState, Lifecycle},
-    planning_view_state::PlanningViewState,
+    delta_view_state::DeltaViewState,
+    diagnostics_view_state::DiagnosticsViewState,
+    help_view_state::HelpViewState,
+    loading_view_state::LoadingViewState,
+    planning_view_state::{PlanningStatus, PlanningViewState},
+    state_view_state::StateViewState,
+    workspace_view_state::{PlannedWorkspace, WorkspaceViewState},
+};
+use tf_client::{RunOutcome, TfClient};
+use tokio::{sync::mpsc, task::JoinHandle};
+use widget::{
+    app_view::AppView, delta_view::DeltaView, diagnostics_view::DiagnosticsView,
+    help_view::HelpView, loading_view::LoadingView, planning_view::PlanningView,
+    state_view::StateView,
 };
-use tf_client::TfClient;
-use tokio::task::JoinHandle;
-use widget::{app_view::AppView, planning_view::PlanningView};
 
+mod config;
+mod config_watcher;
+mod keymap;
 mod model;
+mod plan_output;
+mod policy;
+mod report;
 mod state;
 mod tf_client;
 mod widget;
 
-use crate::model::tf_plan::TfPlan;
+use crate::keymap::{Action, KeyContext, Keymap};
+use crate::model::{
+    tf_event::TfEventDiagnostic,
+    tf_plan::TfPlan,
+    tf_state::{TfState, TfStateFile},
+    trowel_delta::TrowelDelta,
+    trowel_state::TrowelState,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about = "A TUI for working with OpenTofu and Terraform", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(short, long, help = "A path to a plan file (binary or JSON)")]
     plan_file: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with = "plan_file",
+        help = "Keep the binary plan at this path so it can be applied later"
+    )]
+    save_plan: Option<PathBuf>,
     #[arg(
         short,
         long,
+        global = true,
         default_value = "tofu",
         help = "The name/path of a TF binary"
     )]
@@ -42,73 +88,349 @@ struct Args {
         help = "Disable big red warning"
     )]
     hide_experimental_warning: bool,
+    #[arg(
+        long,
+        action,
+        default_value_t = false,
+        conflicts_with_all = ["plan_file", "save_plan"],
+        help = "Plan again whenever configuration in the working directory changes"
+    )]
+    watch: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Browse the resources in the current state without planning
+    State {
+        #[arg(help = "A .tfstate file to read instead of running show")]
+        state_file: Option<PathBuf>,
+    },
+    /// Compare the changes made by two plans, e.g. before and after a review fix
+    Diff {
+        #[arg(help = "The earlier plan file (binary or JSON)")]
+        old: PathBuf,
+        #[arg(help = "The later plan file (binary or JSON)")]
+        new: PathBuf,
+    },
+    /// Print a summary of a plan without starting the TUI, e.g. for CI
+    Summary {
+        #[arg(
+            short,
+            long,
+            help = "A path to a plan file (binary or JSON), instead of planning"
+        )]
+        plan_file: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = SummaryFormat::Markdown)]
+        format: SummaryFormat,
+    },
+    /// Write a report of a plan to a file, for reviewers who don't use a terminal
+    Report {
+        #[arg(
+            short,
+            long,
+            help = "A path to a plan file (binary or JSON), instead of planning"
+        )]
+        plan_file: Option<PathBuf>,
+        #[arg(long, help = "Write a self-contained HTML page to this path")]
+        html: PathBuf,
+    },
+    /// Check a plan against a policy file, exiting with status 2 if it breaks any rules
+    Check {
+        #[arg(
+            short,
+            long,
+            help = "A path to a plan file (binary or JSON), instead of planning"
+        )]
+        plan_file: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value = "trowel-policy.toml",
+            help = "A path to the policy file"
+        )]
+        policy: PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum SummaryFormat {
+    /// Collapsible sections suitable for pull request comments
+    Markdown,
+    /// A stable, versioned schema for other tools to consume
+    Json,
 }
 
 #[tokio::main]
+#[allow(clippy::unwrap_in_result)] // Triggered by the expansion of tokio::main
 async fn main() -> Result<(), Box<dyn Error>> {
     color_eyre::install()?;
 
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::State { state_file }) => return browse_state(args.binary, state_file).await,
+        Some(Command::Diff { old, new }) => return compare_plans(args.binary, old, new).await,
+        Some(Command::Summary { plan_file, format }) => {
+            return print_summary(args.binary, plan_file, format).await;
+        }
+        Some(Command::Report { plan_file, html }) => {
+            return write_report(args.binary, plan_file, html).await;
+        }
+        Some(Command::Check { plan_file, policy }) => {
+            return check_policy(args.binary, plan_file, policy).await;
+        }
+        None => (),
+    }
+
     let plan_file = args.plan_file;
     let show_experimental_warning = !args.hide_experimental_warning;
+    let keymap = Config::load()?.keymap()?;
 
-    let tf_client = TfClient::new(args.binary);
+    // JSON plans can be viewed without a binary, so only binaries that will be run are checked
+    let needs_binary = plan_file.as_ref().is_none_or(|f| !is_json_file(f));
+    let tf_client = if needs_binary {
+        TfClient::detect(args.binary).await?
+    } else {
+        TfClient::new(args.binary)
+    };
 
     // Initialization is deferred to avoid delay between init and first paint
     let mut terminal: Option<Terminal<_>> = None;
 
-    let (binary_tempfile, plan_file) = match plan_file {
+    let (plan_output, plan_file) = match plan_file {
         Some(f) => (None, f),
         None => {
-            let (mut planning_view_state, tx) = PlanningViewState::new();
-            let tf_client = tf_client.clone();
-            let handle = tokio::spawn(async move { tf_client.plan(tx).await });
-            let mut term = terminal.unwrap_or_else(ratatui::init);
-            run_app_preinit(&mut term, &mut planning_view_state, &handle).await?;
+            let plan_output = PlanOutput::new(args.save_plan)?;
+            let out = plan_output.path().to_path_buf();
+            let mut term = terminal.unwrap_or_else(init_terminal);
+            let end = run_planning(&mut term, &tf_client, &out, &keymap).await?;
+            if end != PlanningEnd::Planned {
+                plan_output.discard()?;
+                restore_terminal();
+                // Scripts can tell a failed or interrupted plan apart from a session that ended
+                // because the user didn't want to run init
+                if end == PlanningEnd::Unsuccessful {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            let plan_output = plan_output.keep()?;
             terminal = Some(term);
-            let tempfile = handle.await??;
-            let plan = tempfile.path().to_path_buf();
-            (Some(tempfile), plan)
+            let plan = plan_output.path().to_path_buf();
+            (Some(plan_output), plan)
+        }
+    };
+
+    let mut terminal = terminal.unwrap_or_else(init_terminal);
+    let (diff, text_plan) = run_loading(&mut terminal, &tf_client, &plan_file).await?;
+    // Workspaces can only be re-planned when trowel is doing the planning
+    let workspace_view_state = match plan_output {
+        Some(_) => tf_client.workspaces().await.ok().map(|(names, current)| {
+            let planned = PlannedWorkspace {
+                diff: diff.clone(),
+                text_plan: text_plan.clone(),
+            };
+            WorkspaceViewState::new(names, current, planned)
+        }),
+        None => None,
+    };
+    let saved_plan = match plan_output {
+        Some(PlanOutput::Saved(path)) => Some(path),
+        Some(output) => {
+            // Temporary plans are only needed until they've been shown
+            output.discard()?;
+            None
         }
+        None => None,
     };
 
-    let diff = generate_diff(&tf_client, &plan_file)?;
-    let text_plan = generate_text_plan(&tf_client, &plan_file)?;
-    if let Some(tempfile) = binary_tempfile {
-        // NamedTempFile automatically deletes its tempfile when dropped via its destructor, and so should be dropped explicitly
-        drop(tempfile);
+    let watcher = if args.watch {
+        Some(ConfigWatcher::new(&env::current_dir()?)?)
+    } else {
+        None
     };
 
-    let mut terminal = terminal.unwrap_or_else(ratatui::init);
-    let mut app = AppState::new(diff, text_plan, show_experimental_warning);
-    run_app(&mut terminal, &mut app).await?;
-    ratatui::restore();
+    let mut app = AppState::new(
+        diff,
+        text_plan,
+        show_experimental_warning,
+        tf_client.version().cloned(),
+        workspace_view_state,
+        watcher.is_some(),
+        keymap,
+    );
+    run_app(&mut terminal, &mut app, &tf_client, watcher).await?;
+    restore_terminal();
+
+    if let Some(path) = saved_plan {
+        println!("Plan saved to {}", path.display());
+    }
 
     Ok(())
 }
 
 type TextPlan = String;
 
-fn generate_diff(client: &TfClient, plan_file: &PathBuf) -> Result<TrowelDiff, io::Error> {
+/// Sets up the terminal like `ratatui::init`, and also captures the mouse so that views can be
+/// clicked and scrolled
+fn init_terminal() -> DefaultTerminal {
+    let terminal = ratatui::init();
+    // Everything can still be done from the keyboard if the terminal doesn't support the mouse
+    execute!(io::stdout(), EnableMouseCapture).ok();
+    terminal
+}
+
+fn restore_terminal() {
+    execute!(io::stdout(), DisableMouseCapture).ok();
+    ratatui::restore();
+}
+
+async fn generate_diff(client: &TfClient, plan_file: &PathBuf) -> Result<TrowelDiff, io::Error> {
     let json_plan = if is_json_file(plan_file) {
         fs::read_to_string(plan_file)?
     } else {
-        client.show_as_json(plan_file)?
+        client.show_as_json(plan_file).await?
     };
     let parsed: TfPlan = serde_json::from_str(&json_plan)?;
     let diff = TrowelDiff::from_tf_plan(&parsed)?;
     Ok(diff)
 }
 
-fn generate_text_plan(
+async fn browse_state(binary: String, state_file: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let state = match state_file {
+        // State files are read directly, so no binary or initialized working directory is needed
+        Some(path) => {
+            let parsed: TfStateFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+            TrowelState::from_state_file(&parsed)?
+        }
+        None => {
+            let client = TfClient::detect(binary).await?;
+            let parsed: TfState = serde_json::from_str(&client.show_state_as_json().await?)?;
+            TrowelState::from_tf_state(&parsed)
+        }
+    };
+
+    let keymap = Config::load()?.keymap()?;
+    let mut terminal = init_terminal();
+    let mut state = StateViewState::new(state, keymap);
+    let result = run_state(&mut terminal, &mut state).await;
+    restore_terminal();
+    Ok(result?)
+}
+
+async fn compare_plans(binary: String, old: PathBuf, new: PathBuf) -> Result<(), Box<dyn Error>> {
+    let needs_binary = !is_json_file(&old) || !is_json_file(&new);
+    let client = if needs_binary {
+        TfClient::detect(binary).await?
+    } else {
+        TfClient::new(binary)
+    };
+    let (old_diff, new_diff) =
+        tokio::try_join!(generate_diff(&client, &old), generate_diff(&client, &new))?;
+
+    let keymap = Config::load()?.keymap()?;
+    let mut terminal = init_terminal();
+    let mut state = DeltaViewState::new(TrowelDelta::from_diffs(&old_diff, &new_diff), keymap);
+    let result = run_delta(&mut terminal, &mut state).await;
+    restore_terminal();
+    Ok(result?)
+}
+
+/// Loads the given plan, or plans the working directory without showing any progress
+async fn load_or_plan(binary: String, plan_file: Option<PathBuf>) -> Result<TrowelDiff, io::Error> {
+    let diff = match plan_file {
+        Some(path) => {
+            let client = if is_json_file(&path) {
+                TfClient::new(binary)
+            } else {
+                TfClient::detect(binary).await?
+            };
+            generate_diff(&client, &path).await?
+        }
+        None => {
+            let client = TfClient::detect(binary).await?;
+            let plan_output = PlanOutput::new(None)?;
+            // Progress events aren't shown, so the receiving end is dropped straight away
+            let (tx, _) = mpsc::channel(1);
+            let (_interrupt_tx, interrupt_rx) = mpsc::channel(1);
+            let outcome = client.plan(plan_output.path(), tx, interrupt_rx).await?;
+            let diff = match outcome {
+                RunOutcome::Succeeded => {
+                    generate_diff(&client, &plan_output.path().to_path_buf()).await
+                }
+                RunOutcome::Failed(diagnostics) => {
+                    for d in diagnostics.iter().filter(|d| d.is_error()) {
+                        eprintln!("Error: {}\n{}\n", d.summary, d.detail);
+                    }
+                    Err(io::Error::other("Plan failed"))
+                }
+                RunOutcome::Interrupted => Err(io::Error::other("Plan was interrupted")),
+            };
+            plan_output.discard()?;
+            diff?
+        }
+    };
+    Ok(diff)
+}
+
+async fn print_summary(
+    binary: String,
+    plan_file: Option<PathBuf>,
+    format: SummaryFormat,
+) -> Result<(), Box<dyn Error>> {
+    let diff = load_or_plan(binary, plan_file).await?;
+    let summary = match format {
+        SummaryFormat::Markdown => report::markdown::to_markdown(&diff),
+        SummaryFormat::Json => report::json::to_json(&diff)? + "\n",
+    };
+    print!("{}", summary);
+    Ok(())
+}
+
+async fn write_report(
+    binary: String,
+    plan_file: Option<PathBuf>,
+    html: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let diff = load_or_plan(binary, plan_file).await?;
+    fs::write(&html, report::html::to_html(&diff))?;
+    println!("Report written to {}", html.display());
+    Ok(())
+}
+
+async fn check_policy(
+    binary: String,
+    plan_file: Option<PathBuf>,
+    policy: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    // The policy is loaded first so that mistakes in it are reported without waiting for a plan
+    let policy = Policy::load(&policy)?;
+    let diff = load_or_plan(binary, plan_file).await?;
+
+    let violations = policy.violations(&diff);
+    if violations.is_empty() {
+        println!(
+            "Policy check passed: {} resources changed, {} rules checked",
+            diff.entries().len(),
+            policy.rule_count()
+        );
+        return Ok(());
+    }
+    println!("Policy check failed with {} violations:", violations.len());
+    for v in violations {
+        println!("  - {}", v);
+    }
+    // Errors exit with status 1, so CI can tell a plan that broke the rules apart from a failure
+    std::process::exit(2);
+}
+
+async fn generate_text_plan(
     client: &TfClient,
     plan_file: &PathBuf,
 ) -> Result<Option<TextPlan>, io::Error> {
     let text_plan = if is_json_file(plan_file) {
         None
     } else {
-        Some(client.show_as_text(plan_file)?)
+        Some(client.show_as_text(plan_file).await?)
     };
     Ok(text_plan)
 }
@@ -122,17 +444,25 @@ async fn run_app_preinit<B: Backend, T>(
     terminal: &mut Terminal<B>,
     planning_view_state: &mut PlanningViewState,
     handle: &JoinHandle<T>,
+    keymap: &Keymap,
 ) -> io::Result<()> {
+    const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
     // Painting immediately prevents blank screen
-    terminal.draw(|f| ui_preinit(f, planning_view_state))?;
+    terminal.draw(|f| ui_preinit(f, planning_view_state, keymap))?;
 
+    let mut input_poll = tokio::time::interval(INPUT_POLL_INTERVAL);
     loop {
         tokio::select! {
-            Some(()) = planning_view_state.next_line() => {
-                terminal.draw(|f| ui_preinit(f, planning_view_state))?;
+            Some(()) = planning_view_state.next_event() => {
+                terminal.draw(|f| ui_preinit(f, planning_view_state, keymap))?;
             }
-            else => {
-                if handle.is_finished() {
+            _ = input_poll.tick() => {
+                while event::poll(Duration::ZERO)? {
+                    planning_view_state.process_event(event::read()?, keymap);
+                    terminal.draw(|f| ui_preinit(f, planning_view_state, keymap))?;
+                }
+                if handle.is_finished() && planning_view_state.is_drained() {
                     return Ok(())
                 }
             }
@@ -140,23 +470,367 @@ async fn run_app_preinit<B: Backend, T>(
     }
 }
 
-async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut AppState) -> io::Result<()> {
+/// How planning with a progress view ended
+#[derive(PartialEq)]
+enum PlanningEnd {
+    Planned,
+    /// The working directory wasn't initialized and the user chose not to run init
+    InitDeclined,
+    /// The plan or init failed or was interrupted, which the user has already been shown
+    Unsuccessful,
+}
+
+/// Plans with a progress view, offering to run init if the working directory isn't initialized
+async fn run_planning<B: Backend>(
+    terminal: &mut Terminal<B>,
+    client: &TfClient,
+    out: &Path,
+    keymap: &Keymap,
+) -> Result<PlanningEnd, Box<dyn Error>> {
+    let (mut state, mut tx, mut interrupt_rx) = PlanningViewState::new(client.version().cloned());
     loop {
-        match app.lifecycle {
+        let plan_client = client.clone();
+        let plan_out = out.to_path_buf();
+        let handle =
+            tokio::spawn(async move { plan_client.plan(&plan_out, tx, interrupt_rx).await });
+        run_app_preinit(terminal, &mut state, &handle, keymap).await?;
+        match handle.await?? {
+            RunOutcome::Succeeded => return Ok(PlanningEnd::Planned),
+            RunOutcome::Failed(diagnostics)
+                if diagnostics.iter().any(TfEventDiagnostic::requires_init) =>
+            {
+                state.status = PlanningStatus::InitRequired;
+                if !run_init_prompt(terminal, &mut state, keymap).await? {
+                    return Ok(PlanningEnd::InitDeclined);
+                }
+                let (init_tx, init_interrupt_rx) = state.restart(PlanningStatus::Initializing);
+                let init_client = client.clone();
+                let handle =
+                    tokio::spawn(async move { init_client.init(init_tx, init_interrupt_rx).await });
+                run_app_preinit(terminal, &mut state, &handle, keymap).await?;
+                match handle.await?? {
+                    RunOutcome::Succeeded => {
+                        (tx, interrupt_rx) = state.restart(PlanningStatus::Running);
+                    }
+                    outcome => {
+                        run_unsuccessful(terminal, &mut state, outcome, keymap).await?;
+                        return Ok(PlanningEnd::Unsuccessful);
+                    }
+                }
+            }
+            outcome => {
+                run_unsuccessful(terminal, &mut state, outcome, keymap).await?;
+                return Ok(PlanningEnd::Unsuccessful);
+            }
+        }
+    }
+}
+
+/// Shows why a plan or init didn't succeed until the user quits
+async fn run_unsuccessful<B: Backend>(
+    terminal: &mut Terminal<B>,
+    planning_view_state: &mut PlanningViewState,
+    outcome: RunOutcome,
+    keymap: &Keymap,
+) -> io::Result<()> {
+    match outcome {
+        RunOutcome::Succeeded => Ok(()),
+        RunOutcome::Failed(diagnostics) => {
+            let mut state = DiagnosticsViewState::new(diagnostics);
+            run_diagnostics(terminal, &mut state, keymap).await
+        }
+        RunOutcome::Interrupted => {
+            planning_view_state.status = PlanningStatus::Interrupted;
+            run_interrupted(terminal, planning_view_state, keymap).await
+        }
+    }
+}
+
+/// Waits for the user to either run init or quit, returning whether init should be run
+async fn run_init_prompt<B: Backend>(
+    terminal: &mut Terminal<B>,
+    planning_view_state: &mut PlanningViewState,
+    keymap: &Keymap,
+) -> io::Result<bool> {
+    loop {
+        terminal.draw(|f| ui_preinit(f, planning_view_state, keymap))?;
+        if let Event::Key(key) = event::read()?
+            && matches!(key.kind, KeyEventKind::Press)
+            && !planning_view_state.help.process_keypress(&key, keymap)
+        {
+            if keymap.is_quit(&key) {
+                return Ok(false);
+            } else if keymap.action(KeyContext::Planning, &key) == Some(Action::RunInit) {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+async fn run_interrupted<B: Backend>(
+    terminal: &mut Terminal<B>,
+    planning_view_state: &mut PlanningViewState,
+    keymap: &Keymap,
+) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| ui_preinit(f, planning_view_state, keymap))?;
+        if let Event::Key(key) = event::read()?
+            && matches!(key.kind, KeyEventKind::Press)
+            && !planning_view_state.help.process_keypress(&key, keymap)
+            && keymap.is_quit(&key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+async fn run_loading<B: Backend>(
+    terminal: &mut Terminal<B>,
+    client: &TfClient,
+    plan_file: &PathBuf,
+) -> Result<(TrowelDiff, Option<TextPlan>), io::Error> {
+    const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+    let mut state = LoadingViewState::new();
+    let diff = generate_diff(client, plan_file);
+    let text_plan = generate_text_plan(client, plan_file);
+    tokio::pin!(diff, text_plan);
+
+    // Both show calls run concurrently, with the view redrawn as each one finishes
+    let mut spinner = tokio::time::interval(SPINNER_INTERVAL);
+    let (mut diff_out, mut text_plan_out) = (None, None);
+    while diff_out.is_none() || text_plan_out.is_none() {
+        terminal.draw(|f| ui_loading(f, &mut state))?;
+        tokio::select! {
+            d = &mut diff, if diff_out.is_none() => {
+                diff_out = Some(d?);
+                state.diff_loaded = true;
+            }
+            t = &mut text_plan, if text_plan_out.is_none() => {
+                text_plan_out = Some(t?);
+                state.text_plan_loaded = true;
+            }
+            _ = spinner.tick() => state.tick(),
+        }
+    }
+
+    match (diff_out, text_plan_out) {
+        (Some(diff), Some(text_plan)) => Ok((diff, text_plan)),
+        _ => Err(io::Error::other("Plan finished loading without a result")),
+    }
+}
+
+/// A plan run in the background while the app is open
+enum BackgroundPlan {
+    /// Planning a workspace chosen in the workspace panel
+    Workspace(String),
+    /// Planning the displayed workspace again because configuration changed
+    Replan(Option<String>),
+}
+
+async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut AppState,
+    client: &TfClient,
+    mut watcher: Option<ConfigWatcher>,
+) -> io::Result<()> {
+    const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    let (result_tx, mut result_rx) = mpsc::channel(1);
+    let mut running_plan: Option<mpsc::Sender<()>> = None;
+    let mut replan_pending = false;
+    let mut input_poll = tokio::time::interval(INPUT_POLL_INTERVAL);
+    let mut redraw = true;
+    loop {
+        if let Lifecycle::Quit = app.lifecycle {
+            // A background plan is interrupted rather than abandoned so that it can release its state lock
+            if let Some(interrupt_tx) = running_plan.take() {
+                interrupt_tx.send(()).await.ok();
+                result_rx.recv().await;
+            }
+            return Ok(());
+        }
+
+        // Plans run one at a time, since each plan may take a state lock
+        if running_plan.is_none() {
+            let next = if replan_pending {
+                replan_pending = false;
+                Some(BackgroundPlan::Replan(app.start_replan()))
+            } else {
+                app.workspace_view_state
+                    .as_mut()
+                    .and_then(WorkspaceViewState::start_next_plan)
+                    .map(BackgroundPlan::Workspace)
+            };
+            if let Some(next) = next {
+                let (interrupt_tx, interrupt_rx) = mpsc::channel(1);
+                let client = match &next {
+                    BackgroundPlan::Workspace(name) | BackgroundPlan::Replan(Some(name)) => {
+                        client.in_workspace(name)
+                    }
+                    BackgroundPlan::Replan(None) => client.clone(),
+                };
+                let result_tx = result_tx.clone();
+                tokio::spawn(async move {
+                    let result = plan_workspace(&client, interrupt_rx).await;
+                    result_tx.send((next, result)).await.ok();
+                });
+                running_plan = Some(interrupt_tx);
+                redraw = true;
+            }
+        }
+
+        if redraw {
+            terminal.draw(|f| ui(f, app))?;
+            redraw = false;
+        }
+        tokio::select! {
+            Some((plan, result)) = result_rx.recv() => {
+                running_plan = None;
+                match plan {
+                    BackgroundPlan::Workspace(name) => app.finish_workspace_plan(&name, result),
+                    BackgroundPlan::Replan(workspace) => app.finish_replan(workspace, result),
+                }
+                redraw = true;
+            }
+            () = config_settled(&mut watcher) => {
+                replan_pending = true;
+            }
+            _ = input_poll.tick() => {
+                while event::poll(Duration::ZERO)? {
+                    app.process_event(event::read()?);
+                    redraw = true;
+                }
+            }
+        }
+    }
+}
+
+/// Resolves when watched configuration has changed, or never when not watching
+async fn config_settled(watcher: &mut Option<ConfigWatcher>) {
+    match watcher {
+        Some(watcher) => watcher.settled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Plans without a planning view, for workspaces other than the one planned at startup
+async fn plan_workspace(
+    client: &TfClient,
+    interrupt_rx: mpsc::Receiver<()>,
+) -> Result<PlannedWorkspace, String> {
+    let plan_output = PlanOutput::new(None).map_err(|e| e.to_string())?;
+    // Progress events aren't shown, so the receiving end is dropped straight away
+    let (tx, _) = mpsc::channel(1);
+    let outcome = client
+        .plan(plan_output.path(), tx, interrupt_rx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let planned = match outcome {
+        RunOutcome::Succeeded => {
+            let plan_file = plan_output.path().to_path_buf();
+            let (diff, text_plan) = tokio::try_join!(
+                generate_diff(client, &plan_file),
+                generate_text_plan(client, &plan_file)
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(PlannedWorkspace { diff, text_plan })
+        }
+        RunOutcome::Failed(diagnostics) => Err(diagnostics
+            .iter()
+            .filter(|d| d.is_error())
+            .map(|d| d.summary.clone())
+            .collect::<Vec<_>>()
+            .join("; ")),
+        RunOutcome::Interrupted => Err("interrupted".to_string()),
+    };
+    plan_output.discard().map_err(|e| e.to_string())?;
+    planned
+}
+
+async fn run_diagnostics<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut DiagnosticsViewState,
+    keymap: &Keymap,
+) -> io::Result<()> {
+    loop {
+        match state.lifecycle {
             Lifecycle::Running => {
-                terminal.draw(|f| ui(f, app))?;
-                app.process_event(event::read()?);
+                terminal.draw(|f| ui_diagnostics(f, state, keymap))?;
+                state.process_event(event::read()?, keymap);
             }
             Lifecycle::Quit => return Ok(()),
         }
     }
 }
 
-fn ui_preinit(frame: &mut Frame, state: &mut PlanningViewState) {
-    let ui = PlanningView::new();
+fn ui_preinit(frame: &mut Frame, state: &mut PlanningViewState, keymap: &Keymap) {
+    let ui = PlanningView::new(keymap);
+    frame.render_stateful_widget(ui, frame.area(), state);
+    ui_help(frame, &mut state.help, keymap, KeyContext::Planning);
+}
+
+fn ui_loading(frame: &mut Frame, state: &mut LoadingViewState) {
+    let ui = LoadingView::new();
     frame.render_stateful_widget(ui, frame.area(), state);
 }
 
+async fn run_delta<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut DeltaViewState,
+) -> io::Result<()> {
+    loop {
+        match state.lifecycle {
+            Lifecycle::Running => {
+                terminal.draw(|f| ui_delta(f, state))?;
+                state.process_event(event::read()?);
+            }
+            Lifecycle::Quit => return Ok(()),
+        }
+    }
+}
+
+fn ui_delta(frame: &mut Frame, state: &mut DeltaViewState) {
+    let ui = DeltaView::new();
+    frame.render_stateful_widget(ui, frame.area(), state);
+    ui_help(frame, &mut state.help, &state.keymap, KeyContext::Tree);
+}
+
+async fn run_state<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut StateViewState,
+) -> io::Result<()> {
+    loop {
+        match state.lifecycle {
+            Lifecycle::Running => {
+                terminal.draw(|f| ui_state(f, state))?;
+                state.process_event(event::read()?);
+            }
+            Lifecycle::Quit => return Ok(()),
+        }
+    }
+}
+
+fn ui_state(frame: &mut Frame, state: &mut StateViewState) {
+    let ui = StateView::new();
+    frame.render_stateful_widget(ui, frame.area(), state);
+    ui_help(frame, &mut state.help, &state.keymap, KeyContext::Tree);
+}
+
+fn ui_diagnostics(frame: &mut Frame, state: &mut DiagnosticsViewState, keymap: &Keymap) {
+    let ui = DiagnosticsView::new(keymap);
+    frame.render_stateful_widget(ui, frame.area(), state);
+    ui_help(frame, &mut state.help, keymap, KeyContext::Diagnostics);
+}
+
+/// Shows the keys for a screen that can't be switched from over it, if they've been asked for
+fn ui_help(frame: &mut Frame, state: &mut HelpViewState, keymap: &Keymap, context: KeyContext) {
+    if state.shown {
+        let ui = HelpView::new(keymap, context, false);
+        frame.render_stateful_widget(ui, frame.area(), state);
+    }
+}
+
 fn ui(frame: &mut Frame, state: &mut AppState) {
     let ui = AppView::new();
     frame.render_stateful_widget(ui, frame.area(), state);
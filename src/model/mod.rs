@@ -1,3 +1,10 @@
+pub mod text_search;
+pub mod tf_event;
 pub mod tf_plan;
+pub mod tf_state;
+pub mod tf_version;
+pub mod trowel_delta;
 pub mod trowel_diff;
+pub mod trowel_filter;
+pub mod trowel_state;
 pub mod verb;
@@ -0,0 +1,117 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+};
+
+/// Whether the text contains the query, ignoring ASCII case. An empty query matches nothing.
+pub fn contains_match(text: &str, query: &str) -> bool {
+    !match_ranges(text, query).is_empty()
+}
+
+/// The byte ranges of every non-overlapping occurrence of the query, ignoring ASCII case
+pub fn match_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    // ASCII lowercasing keeps byte offsets the same, so they can be used to slice the original
+    let text = text.to_ascii_lowercase();
+    let query = query.to_ascii_lowercase();
+    text.match_indices(&query)
+        .map(|(start, m)| start..start + m.len())
+        .collect()
+}
+
+/// Splits a span so that every occurrence of the query stands out
+pub fn highlight<'a>(span: Span<'a>, query: &str) -> Vec<Span<'a>> {
+    let ranges = match_ranges(&span.content, query);
+    if ranges.is_empty() {
+        return vec![span];
+    }
+
+    let highlighted = span.style.patch(
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut out = Vec::new();
+    let mut end = 0;
+    for range in ranges {
+        if let Some(before) = span.content.get(end..range.start)
+            && !before.is_empty()
+        {
+            out.push(Span::styled(before.to_string(), span.style));
+        }
+        if let Some(matched) = span.content.get(range.clone()) {
+            out.push(Span::styled(matched.to_string(), highlighted));
+        }
+        end = range.end;
+    }
+    if let Some(after) = span.content.get(end..)
+        && !after.is_empty()
+    {
+        out.push(Span::styled(after.to_string(), span.style));
+    }
+    out
+}
+
+/// Whether the text matches a pattern in which `*` matches any run of characters
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // There's no `*`, so the whole text must have been matched
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.split_once(part) {
+            Some((_, after)) => rest = after,
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_ranges() {
+        assert_eq!(match_ranges("Apple apple", "APPLE"), vec![0..5, 6..11]);
+        assert_eq!(match_ranges("aaaa", "aa"), vec![0..2, 2..4]);
+        assert!(match_ranges("apple", "").is_empty());
+        assert!(!contains_match("apple", "pear"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let style = Style::default().fg(Color::Green);
+        let spans = highlight(Span::styled("random_pet.apple", style), "pet");
+        let contents: Vec<_> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(contents, vec!["random_", "pet", ".apple"]);
+        assert_eq!(spans[0].style, style);
+        assert_eq!(spans[1].style.bg, Some(Color::Yellow));
+
+        let spans = highlight(Span::styled("apple", style), "pear");
+        assert_eq!(spans, vec![Span::styled("apple", style)]);
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("aws_db_instance", "aws_db_instance"));
+        assert!(!glob_matches("aws_db", "aws_db_instance"));
+        assert!(glob_matches("aws_db_*", "aws_db_instance"));
+        assert!(glob_matches("*_instance", "aws_db_instance"));
+        assert!(glob_matches("aws_*_*", "aws_db_instance"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("aws_*_instance", "aws_instance"));
+        assert!(!glob_matches("google_*", "aws_db_instance"));
+    }
+}
@@ -0,0 +1,267 @@
+#![allow(dead_code)] // Unused properties are kept since they're part of the TF machine-readable UI spec
+
+use serde::Deserialize;
+
+/// A single line of output from a TF command run with `-json`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEvent {
+    #[serde(rename = "@level")]
+    pub level: String,
+    #[serde(rename = "@message")]
+    pub message: String,
+    #[serde(rename = "@module")]
+    pub module: String,
+    #[serde(rename = "@timestamp")]
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub body: TfEventBody,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TfEventBody {
+    RefreshStart {
+        hook: TfEventHook,
+    },
+    RefreshComplete {
+        hook: TfEventHook,
+    },
+    EphemeralOpStart {
+        hook: TfEventHook,
+    },
+    EphemeralOpComplete {
+        hook: TfEventHook,
+    },
+    PlannedChange {
+        change: TfEventChange,
+    },
+    ChangeSummary {
+        changes: TfEventChangeSummary,
+    },
+    Diagnostic {
+        diagnostic: TfEventDiagnostic,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventHook {
+    pub resource: TfEventResource,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventResource {
+    pub addr: String,
+    pub module: String,
+    pub resource: String,
+    pub implied_provider: String,
+    pub resource_type: String,
+    pub resource_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventChange {
+    pub resource: TfEventResource,
+    pub action: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventChangeSummary {
+    pub add: usize,
+    pub change: usize,
+    pub remove: usize,
+    pub import: Option<usize>,
+    pub forget: Option<usize>,
+    pub operation: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventDiagnostic {
+    pub severity: String,
+    pub summary: String,
+    #[serde(default)]
+    pub detail: String,
+    pub address: Option<String>,
+    pub range: Option<TfEventDiagnosticRange>,
+    pub snippet: Option<TfEventDiagnosticSnippet>,
+}
+
+impl TfEventDiagnostic {
+    pub fn from_stderr(stderr: String) -> Self {
+        Self {
+            severity: "error".to_string(),
+            summary: "Process exited with an error".to_string(),
+            detail: stderr,
+            address: None,
+            range: None,
+            snippet: None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == "error"
+    }
+
+    /// Whether the error goes away by running `init`, e.g. because a module or provider is
+    /// missing or the lock file no longer matches the configuration. Neither flavour gives these
+    /// errors a distinct code, but they all tell the user to run init.
+    pub fn requires_init(&self) -> bool {
+        const INIT_COMMANDS: [&str; 2] = ["tofu init", "terraform init"];
+        self.is_error()
+            && INIT_COMMANDS
+                .iter()
+                .any(|c| self.summary.contains(c) || self.detail.contains(c))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventDiagnosticRange {
+    pub filename: String,
+    pub start: TfEventDiagnosticPos,
+    pub end: TfEventDiagnosticPos,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventDiagnosticPos {
+    pub line: usize,
+    pub column: usize,
+    pub byte: usize,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventDiagnosticSnippet {
+    pub context: Option<String>,
+    pub code: String,
+    pub start_line: usize,
+    pub highlight_start_offset: usize,
+    pub highlight_end_offset: usize,
+    #[serde(default)]
+    pub values: Vec<TfEventDiagnosticExpressionValue>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TfEventDiagnosticExpressionValue {
+    pub traversal: String,
+    pub statement: String,
+}
+
+impl TfEvent {
+    pub fn from_line(line: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(line)
+    }
+
+    /// Wraps a line of human-readable output, for binaries that can't stream machine-readable events
+    pub fn from_text(line: String) -> Self {
+        Self {
+            level: "info".to_string(),
+            message: line,
+            module: String::new(),
+            timestamp: String::new(),
+            body: TfEventBody::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_line_refresh_start() {
+        let line = r#"{"@level":"info","@message":"random_pet.apple: Refreshing state... [id=apple]","@module":"tofu.ui","@timestamp":"2025-04-01T12:00:00.000000Z","hook":{"resource":{"addr":"random_pet.apple","module":"","resource":"random_pet.apple","implied_provider":"random","resource_type":"random_pet","resource_name":"apple","resource_key":null},"id_key":"id","id_value":"apple"},"type":"refresh_start"}"#;
+        let event = TfEvent::from_line(line).unwrap();
+        match event.body {
+            TfEventBody::RefreshStart { hook } => {
+                assert_eq!(hook.resource.addr, "random_pet.apple")
+            }
+            _ => panic!("Expected refresh_start event"),
+        }
+    }
+
+    #[test]
+    fn test_from_line_change_summary() {
+        let line = r#"{"@level":"info","@message":"Plan: 1 to add, 2 to change, 3 to destroy.","@module":"tofu.ui","@timestamp":"2025-04-01T12:00:00.000000Z","changes":{"add":1,"change":2,"import":0,"remove":3,"operation":"plan"},"type":"change_summary"}"#;
+        let event = TfEvent::from_line(line).unwrap();
+        assert_eq!(
+            event.body,
+            TfEventBody::ChangeSummary {
+                changes: TfEventChangeSummary {
+                    add: 1,
+                    change: 2,
+                    remove: 3,
+                    import: Some(0),
+                    forget: None,
+                    operation: "plan".to_string(),
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_line_diagnostic() {
+        let line = r#"{"@level":"warn","@message":"Warning: Deprecated attribute","@module":"tofu.ui","@timestamp":"2025-04-01T12:00:00.000000Z","diagnostic":{"severity":"warning","summary":"Deprecated attribute","detail":"Use something else"},"type":"diagnostic"}"#;
+        let event = TfEvent::from_line(line).unwrap();
+        assert_eq!(
+            event.body,
+            TfEventBody::Diagnostic {
+                diagnostic: TfEventDiagnostic {
+                    severity: "warning".to_string(),
+                    summary: "Deprecated attribute".to_string(),
+                    detail: "Use something else".to_string(),
+                    address: None,
+                    range: None,
+                    snippet: None,
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_line_diagnostic_snippet() {
+        let line = r#"{"@level":"error","@message":"Error: Unsupported argument","@module":"tofu.ui","@timestamp":"2025-04-01T12:00:00.000000Z","diagnostic":{"severity":"error","summary":"Unsupported argument","detail":"An argument named \"colour\" is not expected here.","range":{"filename":"apple.tf","start":{"line":3,"column":3,"byte":40},"end":{"line":3,"column":9,"byte":46}},"snippet":{"context":"resource \"random_pet\" \"apple\"","code":"  colour = \"red\"","start_line":3,"highlight_start_offset":2,"highlight_end_offset":8,"values":[]}},"type":"diagnostic"}"#;
+        let event = TfEvent::from_line(line).unwrap();
+        let TfEventBody::Diagnostic { diagnostic } = event.body else {
+            panic!("Expected diagnostic event")
+        };
+        assert!(diagnostic.is_error());
+        let range = diagnostic.range.unwrap();
+        assert_eq!(range.filename, "apple.tf");
+        assert_eq!(range.start.line, 3);
+        let snippet = diagnostic.snippet.unwrap();
+        assert_eq!(snippet.code, "  colour = \"red\"");
+        assert_eq!(snippet.highlight_start_offset, 2);
+        assert_eq!(snippet.highlight_end_offset, 8);
+    }
+
+    #[test]
+    fn test_diagnostic_requires_init() {
+        let line = r#"{"@level":"error","@message":"Error: Module not installed","@module":"terraform.ui","@timestamp":"2025-04-01T12:00:00.000000Z","diagnostic":{"severity":"error","summary":"Module not installed","detail":"This module is not yet installed. Run \"terraform init\" to install all modules required by this configuration."},"type":"diagnostic"}"#;
+        let TfEventBody::Diagnostic { diagnostic } = TfEvent::from_line(line).unwrap().body else {
+            panic!("Expected diagnostic event")
+        };
+        assert!(diagnostic.requires_init());
+
+        let stderr = TfEventDiagnostic::from_stderr(
+            "Error: Backend initialization required, please run \"tofu init\"".to_string(),
+        );
+        assert!(stderr.requires_init());
+
+        let unrelated = TfEventDiagnostic::from_stderr("Error: Unsupported argument".to_string());
+        assert!(!unrelated.requires_init());
+    }
+
+    #[test]
+    fn test_from_line_other() {
+        let line = r#"{"@level":"info","@message":"OpenTofu 1.9.0","@module":"tofu.ui","@timestamp":"2025-04-01T12:00:00.000000Z","tofu":"1.9.0","type":"version","ui":"1.2"}"#;
+        let event = TfEvent::from_line(line).unwrap();
+        assert_eq!(event.body, TfEventBody::Other);
+        assert_eq!(event.message, "OpenTofu 1.9.0");
+    }
+
+    #[test]
+    fn test_from_line_invalid() {
+        assert!(TfEvent::from_line("not json").is_err());
+    }
+}
@@ -10,9 +10,11 @@ use serde_json::{Map, Value};
 pub struct TfPlan {
     pub format_version: String,
     pub terraform_version: String,
+    pub variables: Option<HashMap<String, Value>>,
     pub planned_values: TfPlanPlannedValues,
     pub resource_drift: Option<Vec<Value>>,
     pub resource_changes: Option<Vec<TfPlanResourceChange>>,
+    pub output_changes: Option<HashMap<String, Value>>,
     pub prior_state: Option<TfPlanPriorState>,
     pub configuration: TfPlanConfiguration,
     pub relevant_attributes: Option<Vec<TfPlanRelevantAttribute>>,
@@ -26,9 +28,19 @@ pub struct TfPlan {
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TfPlanPlannedValues {
+    pub outputs: Option<HashMap<String, TfPlanPlannedValuesOutput>>,
     pub root_module: TfPlanPlannedValuesRootModule,
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TfPlanPlannedValuesOutput {
+    pub sensitive: bool,
+    pub value: Option<Value>,
+    #[serde(rename = "type")]
+    pub output_type: Option<Value>,
+}
+
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TfPlanPlannedValuesRootModule {
@@ -44,12 +56,14 @@ pub struct TfPlanPlannedValuesModuleResource {
     #[serde(rename = "type")]
     pub resource_type: String,
     pub name: String,
-    pub index: Option<String>,
+    pub index: Option<Value>,
     pub provider_name: String,
     pub schema_version: u8,
     pub values: HashMap<String, Value>,
     pub sensitive_values: HashMap<String, Value>,
     pub depends_on: Option<Vec<String>>,
+    pub tainted: Option<bool>,
+    pub deposed_key: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -68,7 +82,7 @@ pub struct TfPlanResourceChange {
     #[serde(rename = "type")]
     pub resource_type: String,
     pub name: String,
-    pub index: Option<String>,
+    pub index: Option<Value>,
     pub provider_name: String,
     pub change: TfPlanResourceChangeChange,
     pub action_reason: Option<String>,
@@ -85,7 +99,7 @@ pub struct TfPlanResourceChangeChange {
     pub before_sensitive: SensitiveValues,
     pub after_sensitive: SensitiveValues,
     pub importing: Option<Value>,
-    pub replace_paths: Option<Vec<Vec<String>>>,
+    pub replace_paths: Option<Vec<Vec<Value>>>,
 }
 
 pub type SensitiveValuesInner = Option<HashMap<String, Value>>;
@@ -119,7 +133,7 @@ impl<'de> Deserialize<'de> for SensitiveValues {
             where
                 E: serde::de::Error,
             {
-                if v == false {
+                if !v {
                     Ok(SensitiveValues(None))
                 } else {
                     Err(E::custom("Expected false or a map"))
@@ -210,3 +224,48 @@ pub struct TfPlanCheckInstanceAddress {
     pub module: String,
     pub to_display: String,
 }
+
+/// Plans for tests, written as JSON so that they only need the fields that matter to a test
+#[cfg(test)]
+impl TfPlan {
+    /// A plan making the given resource changes, e.g. from `test_change`
+    pub fn test(resource_changes: Vec<Value>) -> Self {
+        serde_json::from_value(serde_json::json!({
+            "format_version": "1.2",
+            "terraform_version": "1.9.0",
+            "planned_values": {"root_module": {}},
+            "resource_changes": resource_changes,
+            "configuration": {"root_module": {}},
+            "timestamp": "2025-04-01T12:00:00Z",
+            "errored": false
+        }))
+        .unwrap()
+    }
+
+    /// A change to the resource at an address, changing `length` from 2 to 3. The type, provider
+    /// and module come from the address, e.g. `module.network.aws_vpc.main`, and fields can be
+    /// changed afterwards, e.g. `change["change"]["before"]`.
+    pub fn test_change(address: &str, action: &str) -> Value {
+        let mut parts: Vec<_> = address.split('.').collect();
+        let name = parts.pop().unwrap_or_default();
+        let resource_type = parts.pop().unwrap_or("random_pet");
+        let module = (!parts.is_empty()).then(|| parts.join("."));
+        let provider = resource_type.split('_').next().unwrap_or_default();
+        serde_json::json!({
+            "address": address,
+            "module_address": module,
+            "mode": "managed",
+            "type": resource_type,
+            "name": name,
+            "provider_name": format!("registry.opentofu.org/hashicorp/{}", provider),
+            "change": {
+                "actions": [action],
+                "before": {"length": 2},
+                "after": {"length": 3},
+                "after_unknown": {},
+                "before_sensitive": false,
+                "after_sensitive": false
+            }
+        })
+    }
+}
@@ -0,0 +1,157 @@
+#![allow(dead_code)] // Unused properties are kept so that the formats are modelled in full
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::tf_plan::{TfPlanCheck, TfPlanPlannedValues};
+
+/// The output of `show -json` without a plan file, describing the current state
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TfState {
+    pub format_version: String,
+    /// Absent, along with the values, when the state is empty
+    pub terraform_version: Option<String>,
+    pub values: Option<TfStateValues>,
+    pub checks: Option<Vec<TfPlanCheck>>,
+}
+
+/// State uses the same values representation as the planned values of a plan
+pub type TfStateValues = TfPlanPlannedValues;
+
+/// A raw `.tfstate` file, as written by the binary. This is an internal format that gains
+/// fields between releases, so unknown fields are allowed.
+#[derive(Deserialize)]
+pub struct TfStateFile {
+    pub version: u32,
+    pub terraform_version: String,
+    #[serde(default)]
+    pub outputs: HashMap<String, TfStateFileOutput>,
+    #[serde(default)]
+    pub resources: Vec<TfStateFileResource>,
+}
+
+impl TfStateFile {
+    /// The only version written by OpenTofu and Terraform since Terraform 0.12
+    pub const SUPPORTED_VERSION: u32 = 4;
+}
+
+#[derive(Deserialize)]
+pub struct TfStateFileOutput {
+    pub value: Value,
+    #[serde(rename = "type")]
+    pub output_type: Option<Value>,
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+#[derive(Deserialize)]
+pub struct TfStateFileResource {
+    pub module: Option<String>,
+    pub mode: String,
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub name: String,
+    pub provider: String,
+    pub instances: Vec<TfStateFileInstance>,
+}
+
+#[derive(Deserialize)]
+pub struct TfStateFileInstance {
+    pub index_key: Option<Value>,
+    pub status: Option<String>,
+    pub deposed: Option<String>,
+    pub schema_version: u64,
+    pub attributes: Option<HashMap<String, Value>>,
+    /// Paths to sensitive values, each a list of steps such as `{"type":"get_attr","value":"password"}`
+    #[serde(default)]
+    pub sensitive_attributes: Vec<Value>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl TfStateFileResource {
+    /// The address of an instance, formatted the same way as in `show -json`
+    pub fn instance_address(&self, instance: &TfStateFileInstance) -> String {
+        let mut address = String::new();
+        if let Some(module) = &self.module {
+            address.push_str(module);
+            address.push('.');
+        }
+        if self.mode == "data" {
+            address.push_str("data.");
+        }
+        address.push_str(&format!("{}.{}", self.resource_type, self.name));
+        if let Some(index) = &instance.index_key {
+            address.push_str(&format!("[{}]", index));
+        }
+        address
+    }
+}
+
+impl TfStateFileInstance {
+    /// Names of top-level attributes that are sensitive in whole or in part
+    pub fn sensitive_attribute_names(&self) -> Vec<String> {
+        self.sensitive_attributes
+            .iter()
+            .filter_map(|path| path.as_array()?.first()?.get("value")?.as_str())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_empty() {
+        let state: TfState = serde_json::from_str(r#"{"format_version":"1.0"}"#).unwrap();
+        assert!(state.values.is_none());
+    }
+
+    #[test]
+    fn test_state_file_instance_address() {
+        let json = r#"{
+            "version": 4,
+            "terraform_version": "1.9.0",
+            "serial": 1,
+            "lineage": "8a6c7c8e",
+            "resources": [
+                {
+                    "module": "module.fruit",
+                    "mode": "data",
+                    "type": "random_pet",
+                    "name": "apple",
+                    "provider": "provider[\"registry.opentofu.org/hashicorp/random\"]",
+                    "instances": [
+                        {
+                            "index_key": "a",
+                            "schema_version": 0,
+                            "attributes": {"id": "fond-koala", "length": 2},
+                            "sensitive_attributes": [[{"type": "get_attr", "value": "id"}]]
+                        },
+                        {"index_key": 0, "schema_version": 0, "attributes": {}}
+                    ]
+                }
+            ]
+        }"#;
+        let state: TfStateFile = serde_json::from_str(json).unwrap();
+        let resource = &state.resources[0];
+        assert_eq!(
+            resource.instance_address(&resource.instances[0]),
+            "module.fruit.data.random_pet.apple[\"a\"]"
+        );
+        assert_eq!(
+            resource.instance_address(&resource.instances[1]),
+            "module.fruit.data.random_pet.apple[0]"
+        );
+        assert_eq!(
+            resource.instances[0].sensitive_attribute_names(),
+            vec!["id"]
+        );
+        assert!(resource.instances[1].sensitive_attribute_names().is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, io};
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TfFlavour {
+    OpenTofu,
+    Terraform,
+}
+
+impl TfFlavour {
+    /// Identifies the flavour from the first line of the human-readable `version` output
+    pub fn from_version_text(text: &str) -> Result<Self, io::Error> {
+        let first_line = text.lines().next().unwrap_or_default();
+        if first_line.starts_with("OpenTofu") {
+            Ok(Self::OpenTofu)
+        } else if first_line.starts_with("Terraform") {
+            Ok(Self::Terraform)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "Binary does not look like OpenTofu or Terraform: {:?}",
+                    first_line
+                ),
+            ))
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OpenTofu => "OpenTofu",
+            Self::Terraform => "Terraform",
+        }
+    }
+
+    /// The oldest release whose JSON plan format trowel can parse
+    fn minimum_version(&self) -> (u32, u32, u32) {
+        match self {
+            Self::OpenTofu => (1, 6, 0),
+            Self::Terraform => (1, 5, 0),
+        }
+    }
+}
+
+/// The output of `version -json`, which both flavours share
+#[derive(Deserialize)]
+pub struct TfVersionJson {
+    pub terraform_version: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TfVersion {
+    pub flavour: TfFlavour,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub raw: String,
+}
+
+impl TfVersion {
+    pub fn new(flavour: TfFlavour, version_json: &TfVersionJson) -> Result<Self, io::Error> {
+        let raw = version_json.terraform_version.clone();
+        let mut parts = raw.split(['.', '-', '+']).map(|p| p.parse::<u32>().ok());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Some(major)), Some(Some(minor)), Some(Some(patch))) => Ok(Self {
+                flavour,
+                major,
+                minor,
+                patch,
+                raw,
+            }),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to parse version: {}", raw),
+            )),
+        }
+    }
+
+    pub fn check_supported(&self) -> Result<(), io::Error> {
+        let minimum = self.flavour.minimum_version();
+        if self.at_least(minimum) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{} v{} is not supported, please upgrade to v{}.{}.{} or later",
+                    self.flavour.name(),
+                    self.raw,
+                    minimum.0,
+                    minimum.1,
+                    minimum.2
+                ),
+            ))
+        }
+    }
+
+    /// Whether `init -json` emits the machine-readable UI event stream
+    pub fn supports_json_init_streaming(&self) -> bool {
+        match self.flavour {
+            TfFlavour::OpenTofu => self.at_least((1, 10, 0)),
+            TfFlavour::Terraform => self.at_least((1, 9, 0)),
+        }
+    }
+
+    /// Whether plans can contain the "forget" action produced by `removed` blocks
+    pub fn supports_forget(&self) -> bool {
+        self.at_least((1, 7, 0))
+    }
+
+    /// Whether configurations can declare `ephemeral` resources
+    pub fn supports_ephemeral_resources(&self) -> bool {
+        match self.flavour {
+            TfFlavour::OpenTofu => self.at_least((1, 11, 0)),
+            TfFlavour::Terraform => self.at_least((1, 10, 0)),
+        }
+    }
+
+    fn at_least(&self, version: (u32, u32, u32)) -> bool {
+        (self.major, self.minor, self.patch) >= version
+    }
+}
+
+impl fmt::Display for TfVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} v{}", self.flavour.name(), self.raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(flavour: TfFlavour, raw: &str) -> Result<TfVersion, io::Error> {
+        TfVersion::new(
+            flavour,
+            &TfVersionJson {
+                terraform_version: raw.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_flavour_from_version_text() {
+        assert_eq!(
+            TfFlavour::from_version_text("OpenTofu v1.9.0\non linux_amd64").unwrap(),
+            TfFlavour::OpenTofu
+        );
+        assert_eq!(
+            TfFlavour::from_version_text("Terraform v1.5.7\non linux_amd64").unwrap(),
+            TfFlavour::Terraform
+        );
+        assert!(TfFlavour::from_version_text("cowsay 3.04").is_err());
+        assert!(TfFlavour::from_version_text("").is_err());
+    }
+
+    #[test]
+    fn test_version_parse() {
+        let v = version(TfFlavour::OpenTofu, "1.10.0-beta1").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 10, 0));
+        assert_eq!(v.to_string(), "OpenTofu v1.10.0-beta1");
+        assert!(version(TfFlavour::OpenTofu, "1.10").is_err());
+        assert!(version(TfFlavour::OpenTofu, "dev").is_err());
+    }
+
+    #[test]
+    fn test_check_supported() {
+        assert!(
+            version(TfFlavour::OpenTofu, "1.6.0")
+                .unwrap()
+                .check_supported()
+                .is_ok()
+        );
+        assert!(
+            version(TfFlavour::OpenTofu, "1.5.9")
+                .unwrap()
+                .check_supported()
+                .is_err()
+        );
+        assert!(
+            version(TfFlavour::Terraform, "1.4.7")
+                .unwrap()
+                .check_supported()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_feature_gates() {
+        let tofu = version(TfFlavour::OpenTofu, "1.6.2").unwrap();
+        assert!(!tofu.supports_json_init_streaming());
+        assert!(!tofu.supports_forget());
+        assert!(!tofu.supports_ephemeral_resources());
+
+        let tofu = version(TfFlavour::OpenTofu, "1.11.0").unwrap();
+        assert!(tofu.supports_forget());
+        assert!(tofu.supports_ephemeral_resources());
+
+        let terraform = version(TfFlavour::Terraform, "1.10.1").unwrap();
+        assert!(terraform.supports_json_init_streaming());
+        assert!(terraform.supports_forget());
+        assert!(terraform.supports_ephemeral_resources());
+    }
+}
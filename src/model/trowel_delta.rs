@@ -0,0 +1,373 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use tui_tree_widget::TreeItem;
+
+use super::{
+    trowel_diff::{TrowelDiff, TrowelDiffEntry, TrowelDiffEntryBeforeAfter},
+    verb::Verb,
+};
+
+/// How the changes planned for each resource differ between two plans
+pub struct TrowelDelta(Vec<TrowelDeltaEntry>);
+
+impl TrowelDelta {
+    pub fn from_diffs(old: &TrowelDiff, new: &TrowelDiff) -> Self {
+        let by_path = |diff: &TrowelDiff| -> HashMap<String, TrowelDiffEntry> {
+            diff.entries()
+                .iter()
+                .map(|e| (e.resource_path.clone(), e.clone()))
+                .collect()
+        };
+        let old_entries = by_path(old);
+        let new_entries = by_path(new);
+        let paths: BTreeSet<&String> = old_entries.keys().chain(new_entries.keys()).collect();
+
+        let mut out = TrowelDelta(Vec::new());
+        for path in paths {
+            let old = old_entries.get(path);
+            let new = new_entries.get(path);
+            let entry = TrowelDeltaEntry {
+                resource_path: path.clone(),
+                old_verb: old.map(|e| e.verb.clone()),
+                new_verb: new.map(|e| e.verb.clone()),
+                attributes: attribute_changes(old, new),
+            };
+            // Resources changed in exactly the same way by both plans are left out
+            if entry.kind() == TrowelDeltaKind::ValuesChanged && entry.attributes.is_empty() {
+                continue;
+            }
+            out.0.push(entry);
+        }
+        out
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn to_tree_items(&self) -> Result<Vec<TreeItem<'_, String>>, io::Error> {
+        let mut out = vec![];
+
+        for e in &self.0 {
+            let mut attributes = Vec::new();
+            for a in &e.attributes {
+                let identifier = format!("{} {}", e.resource_path, a.name);
+                let item = match (&a.old, &a.new) {
+                    // Both plans have a value, so they're shown one above the other to compare
+                    (Some(old), Some(new)) => TreeItem::new(
+                        identifier.clone(),
+                        Span::styled(
+                            a.name.as_str(),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        vec![
+                            TreeItem::new_leaf(
+                                format!("{} old", identifier),
+                                Self::value_line("old plan: ", old),
+                            ),
+                            TreeItem::new_leaf(
+                                format!("{} new", identifier),
+                                Self::value_line("new plan: ", new),
+                            ),
+                        ],
+                    )?,
+                    (Some(value), None) | (None, Some(value)) => TreeItem::new_leaf(
+                        identifier,
+                        Self::value_line(&format!("{} ", a.name), value),
+                    ),
+                    (None, None) => continue,
+                };
+                attributes.push(item);
+            }
+
+            let item = TreeItem::new(e.resource_path.clone(), Self::resource_line(e), attributes)?;
+            out.push(item);
+        }
+
+        Ok(out)
+    }
+
+    /// Counts of each kind of difference, e.g. for a summary line
+    pub fn kind_uses_fmt(&self) -> Line<'_> {
+        let mut spans = Vec::new();
+        for kind in TrowelDeltaKind::ALL {
+            let count = self.0.iter().filter(|e| e.kind() == kind).count();
+            if count == 0 {
+                continue;
+            }
+            spans.push(Span::from(if spans.is_empty() { " " } else { " | " }));
+            spans.push(Span::styled(
+                format!("{} {}", kind.name_lower(), count),
+                Style::default()
+                    .fg(kind.to_color())
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if !spans.is_empty() {
+            spans.push(Span::from(" "));
+        }
+        Line::from(spans)
+    }
+
+    fn resource_line(entry: &TrowelDeltaEntry) -> Line<'_> {
+        let past_tense =
+            |verb: &Option<Verb>| verb.as_ref().map(Verb::to_past_tense).unwrap_or_default();
+        let (description, note) = match entry.kind() {
+            TrowelDeltaKind::Added => (
+                format!(" will now be {}", past_tense(&entry.new_verb)),
+                " (not in old plan)",
+            ),
+            TrowelDeltaKind::Dropped => (
+                format!(" will no longer be {}", past_tense(&entry.old_verb)),
+                " (not in new plan)",
+            ),
+            TrowelDeltaKind::VerbChanged => (
+                format!(
+                    " will now be {} instead of {}",
+                    past_tense(&entry.new_verb),
+                    past_tense(&entry.old_verb)
+                ),
+                "",
+            ),
+            TrowelDeltaKind::ValuesChanged => (
+                format!(
+                    " will still be {}, with different values",
+                    past_tense(&entry.new_verb)
+                ),
+                "",
+            ),
+        };
+        let color = entry
+            .new_verb
+            .as_ref()
+            .or(entry.old_verb.as_ref())
+            .map(Verb::to_color)
+            .unwrap_or(Color::Gray);
+        Line::from(vec![
+            Span::styled(
+                entry.resource_path.as_str(),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::from(description),
+            Span::styled(note, Style::default().fg(Color::DarkGray)),
+        ])
+    }
+
+    fn value_line<'a>(label: &str, value: &'a TrowelDiffEntryBeforeAfter) -> Line<'a> {
+        Line::from(
+            std::iter::once(Span::from(label.to_string()))
+                .chain(value.fmt())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TrowelDeltaKind {
+    /// Changed by the new plan but not by the old one
+    Added,
+    /// Changed by the old plan but not by the new one
+    Dropped,
+    /// Changed by both plans, but with different actions
+    VerbChanged,
+    /// Changed by both plans with the same action, but with different values
+    ValuesChanged,
+}
+
+impl TrowelDeltaKind {
+    const ALL: [Self; 4] = [
+        Self::Added,
+        Self::Dropped,
+        Self::VerbChanged,
+        Self::ValuesChanged,
+    ];
+
+    pub fn name_lower(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Dropped => "dropped",
+            Self::VerbChanged => "action changed",
+            Self::ValuesChanged => "values changed",
+        }
+    }
+
+    pub fn to_color(&self) -> Color {
+        match self {
+            Self::Added => Color::Green,
+            Self::Dropped => Color::Red,
+            Self::VerbChanged => Color::Yellow,
+            Self::ValuesChanged => Color::Cyan,
+        }
+    }
+}
+
+pub struct TrowelDeltaEntry {
+    pub resource_path: String,
+    pub old_verb: Option<Verb>,
+    pub new_verb: Option<Verb>,
+    pub attributes: Vec<TrowelDeltaAttribute>,
+}
+
+impl TrowelDeltaEntry {
+    pub fn kind(&self) -> TrowelDeltaKind {
+        match (&self.old_verb, &self.new_verb) {
+            (None, _) => TrowelDeltaKind::Added,
+            (_, None) => TrowelDeltaKind::Dropped,
+            (Some(old), Some(new)) if old != new => TrowelDeltaKind::VerbChanged,
+            _ => TrowelDeltaKind::ValuesChanged,
+        }
+    }
+}
+
+/// An attribute whose planned change differs between the plans. A side is `None` when that
+/// plan doesn't change the resource at all, or the resource has no such attribute.
+pub struct TrowelDeltaAttribute {
+    pub name: String,
+    pub old: Option<TrowelDiffEntryBeforeAfter>,
+    pub new: Option<TrowelDiffEntryBeforeAfter>,
+}
+
+fn attribute_changes(
+    old: Option<&TrowelDiffEntry>,
+    new: Option<&TrowelDiffEntry>,
+) -> Vec<TrowelDeltaAttribute> {
+    let names: BTreeSet<&String> = old
+        .iter()
+        .chain(new.iter())
+        .flat_map(|e| e.values.keys())
+        .collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old_value = old.and_then(|e| e.values.get(name));
+            let new_value = new.and_then(|e| e.values.get(name));
+            let differs = match (old, new) {
+                (Some(_), Some(_)) => old_value != new_value,
+                // When only one plan changes the resource, only what that plan changes is of interest
+                _ => old_value.or(new_value).is_some_and(|v| v.changed()),
+            };
+            differs.then(|| TrowelDeltaAttribute {
+                name: name.clone(),
+                old: old_value.cloned(),
+                new: new_value.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::model::tf_plan::TfPlan;
+
+    use super::*;
+
+    /// A plan containing the given resource changes, as `(address, action, before, after)`
+    fn diff(changes: &[(&str, &str, Value, Value)]) -> TrowelDiff {
+        TrowelDiff::test(
+            changes
+                .iter()
+                .map(|(address, action, before, after)| {
+                    let mut change = TfPlan::test_change(address, action);
+                    change["change"]["before"] = before.clone();
+                    change["change"]["after"] = after.clone();
+                    change
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_from_diffs() {
+        let old = diff(&[
+            ("apple", "create", json!(null), json!({"length": 2})),
+            (
+                "banana",
+                "update",
+                json!({"length": 2}),
+                json!({"length": 3}),
+            ),
+            ("cherry", "delete", json!({"length": 2}), json!(null)),
+            (
+                "damson",
+                "update",
+                json!({"length": 2}),
+                json!({"length": 3}),
+            ),
+        ]);
+        let new = diff(&[
+            ("apple", "create", json!(null), json!({"length": 2})),
+            (
+                "banana",
+                "update",
+                json!({"length": 2}),
+                json!({"length": 4}),
+            ),
+            ("damson", "delete", json!({"length": 2}), json!(null)),
+            ("elder", "create", json!(null), json!({"length": 2})),
+        ]);
+        let delta = TrowelDelta::from_diffs(&old, &new);
+
+        let kinds: Vec<_> = delta
+            .0
+            .iter()
+            .map(|e| (e.resource_path.as_str(), e.kind()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("banana", TrowelDeltaKind::ValuesChanged),
+                ("cherry", TrowelDeltaKind::Dropped),
+                ("damson", TrowelDeltaKind::VerbChanged),
+                ("elder", TrowelDeltaKind::Added),
+            ]
+        );
+
+        let banana = &delta.0[0];
+        assert_eq!(banana.attributes.len(), 1);
+        assert_eq!(banana.attributes[0].name, "length");
+        assert!(banana.attributes[0].old.is_some() && banana.attributes[0].new.is_some());
+
+        let elder = &delta.0[3];
+        assert!(elder.attributes[0].old.is_none() && elder.attributes[0].new.is_some());
+    }
+
+    #[test]
+    fn test_from_diffs_identical() {
+        let plan = diff(&[("apple", "create", json!(null), json!({"length": 2}))]);
+        assert!(TrowelDelta::from_diffs(&plan, &plan).is_empty());
+    }
+
+    #[test]
+    fn test_to_tree_items() {
+        let old = diff(&[(
+            "banana",
+            "update",
+            json!({"length": 2}),
+            json!({"length": 3}),
+        )]);
+        let new = diff(&[(
+            "banana",
+            "update",
+            json!({"length": 2}),
+            json!({"length": 4}),
+        )]);
+        let delta = TrowelDelta::from_diffs(&old, &new);
+        let items = delta.to_tree_items().unwrap();
+        assert_eq!(items.len(), 1);
+        let attribute = &items[0].children()[0];
+        assert_eq!(attribute.identifier(), "banana length");
+        let sides: Vec<_> = attribute
+            .children()
+            .iter()
+            .map(|i| i.identifier().as_str())
+            .collect();
+        assert_eq!(sides, vec!["banana length old", "banana length new"]);
+    }
+}
@@ -10,7 +10,9 @@ use serde_json::Value;
 use tui_tree_widget::TreeItem;
 
 use super::{
+    text_search::{contains_match, highlight},
     tf_plan::{TfPlan, TfPlanResourceChangeChange},
+    trowel_filter::TrowelFilter,
     verb::Verb,
 };
 
@@ -42,6 +44,11 @@ impl TrowelDiff {
                     out.0.push(TrowelDiffEntry {
                         verb,
                         resource_path: rc.address.clone(),
+                        resource_type: rc.resource_type.clone(),
+                        provider: rc.provider_name.clone(),
+                        module: rc.module_address.clone(),
+                        action_reason: rc.action_reason.clone(),
+                        replace_paths: rc.change.replace_paths.clone().unwrap_or_default(),
                         values,
                     });
                 }
@@ -51,10 +58,24 @@ impl TrowelDiff {
         Ok(out)
     }
 
-    pub fn to_tree_items(&self) -> Result<Vec<TreeItem<String>>, io::Error> {
+    pub fn entries(&self) -> &[TrowelDiffEntry] {
+        &self.0
+    }
+
+    pub fn entry(&self, resource_path: &str) -> Option<&TrowelDiffEntry> {
+        self.0.iter().find(|e| e.resource_path == resource_path)
+    }
+
+    /// Builds tree items for the entries the filter lets through, highlighting wherever the
+    /// query occurs
+    pub fn to_tree_items(
+        &self,
+        query: &str,
+        filter: &TrowelFilter,
+    ) -> Result<Vec<TreeItem<'_, String>>, io::Error> {
         let mut out = vec![];
 
-        for e in &self.0 {
+        for e in self.0.iter().filter(|e| filter.matches(e)) {
             let mut values = Vec::new();
             let mut unchanged: usize = 0;
 
@@ -64,9 +85,10 @@ impl TrowelDiff {
                     values.push(TreeItem::new_leaf(
                         format!("{} {}", e.resource_path, k),
                         Line::from(
-                            std::iter::once(Span::from(k))
+                            highlight(Span::from(k), query)
+                                .into_iter()
                                 .chain(std::iter::once(Span::from(" ")))
-                                .chain(v.fmt().into_iter())
+                                .chain(v.fmt_highlighted(query))
                                 .collect::<Vec<_>>(),
                         ),
                     ))
@@ -89,19 +111,17 @@ impl TrowelDiff {
             }
 
             // Create TreeItem for resource
-            let item = TreeItem::new(
-                e.resource_path.clone(),
-                Line::from(vec![
-                    Span::styled(
-                        e.resource_path.to_string(),
-                        Style::default()
-                            .fg(e.verb.to_color())
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::from(format!(" will be {}", e.verb.to_past_tense())),
-                ]),
-                values,
-            )?;
+            let mut line = highlight(
+                Span::styled(
+                    e.resource_path.to_string(),
+                    Style::default()
+                        .fg(e.verb.to_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                query,
+            );
+            line.push(Span::from(format!(" will be {}", e.verb.to_past_tense())));
+            let item = TreeItem::new(e.resource_path.clone(), Line::from(line), values)?;
 
             out.push(item);
         }
@@ -109,21 +129,66 @@ impl TrowelDiff {
         Ok(out)
     }
 
-    pub fn verb_uses(&self) -> HashMap<Verb, u8> {
+    /// The identifier path of every node in the tree, in order, with whether it matches the
+    /// query. Resources match on their address, and changed attributes on their name or values.
+    pub fn search(&self, query: &str, filter: &TrowelFilter) -> Vec<(Vec<String>, bool)> {
+        let mut out = Vec::new();
+
+        for e in self.0.iter().filter(|e| filter.matches(e)) {
+            out.push((
+                vec![e.resource_path.clone()],
+                contains_match(&e.resource_path, query),
+            ));
+            let mut unchanged = false;
+            for (k, v) in e.values_sorted() {
+                if v.changed() {
+                    let matches = contains_match(k, query)
+                        || contains_match(&v.before_plaintext(), query)
+                        || contains_match(&v.after_plaintext(), query);
+                    out.push((
+                        vec![
+                            e.resource_path.clone(),
+                            format!("{} {}", e.resource_path, k),
+                        ],
+                        matches,
+                    ));
+                } else {
+                    unchanged = true;
+                }
+            }
+            if unchanged {
+                out.push((
+                    vec![
+                        e.resource_path.clone(),
+                        format!("{} unchanged", e.resource_path),
+                    ],
+                    false,
+                ));
+            }
+        }
+
+        out
+    }
+
+    pub fn verb_uses(&self) -> HashMap<Verb, usize> {
         let mut out = HashMap::new();
         for e in &self.0 {
-            let current = *out.get(&e.verb).unwrap_or(&0);
-            out.insert(e.verb.clone(), current + 1);
+            *out.entry(e.verb.clone()).or_insert(0) += 1;
         }
         out
     }
 
-    pub fn verb_uses_fmt(&self) -> Line {
+    /// How many times each verb is used, in the order verbs are summarised in
+    pub fn verb_uses_sorted(&self) -> Vec<(Verb, usize)> {
+        let mut uses: Vec<_> = self.verb_uses().into_iter().collect();
+        uses.sort_by_key(|(verb, _)| verb.clone());
+        uses
+    }
+
+    pub fn verb_uses_fmt(&self) -> Line<'_> {
         let mut lines = Vec::new();
 
-        let uses = &self.verb_uses();
-        let mut uses: Vec<_> = uses.iter().collect();
-        uses.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let uses = self.verb_uses_sorted();
 
         for (i, (verb, use_count)) in uses.iter().enumerate() {
             if i == 0 {
@@ -152,6 +217,14 @@ impl TrowelDiff {
 pub struct TrowelDiffEntry {
     pub verb: Verb,
     pub resource_path: String,
+    pub resource_type: String,
+    pub provider: String,
+    /// The address of the module containing the resource, or `None` for the root module
+    pub module: Option<String>,
+    /// Why the resource is being replaced or destroyed, e.g. `replace_because_tainted`
+    pub action_reason: Option<String>,
+    /// Paths to the attributes that force the resource to be replaced
+    pub replace_paths: Vec<Vec<Value>>,
     pub values: HashMap<String, TrowelDiffEntryBeforeAfter>,
 }
 
@@ -163,6 +236,33 @@ impl TrowelDiffEntry {
     }
 }
 
+#[cfg(test)]
+impl TrowelDiff {
+    /// The diff of a plan making the given resource changes, e.g. from `TfPlan::test_change`
+    pub fn test(resource_changes: Vec<Value>) -> Self {
+        Self::from_tf_plan(&TfPlan::test(resource_changes)).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl TrowelDiffEntry {
+    /// An entry without attributes, typed and named like those from `TfPlan::test_change`
+    pub fn test(address: &str, verb: Verb) -> Self {
+        let change = TfPlan::test_change(address, "no-op");
+        let text = |field: &str| change[field].as_str().map(String::from);
+        Self {
+            verb,
+            resource_path: address.to_string(),
+            resource_type: text("type").unwrap_or_default(),
+            provider: text("provider_name").unwrap_or_default(),
+            module: text("module_address"),
+            action_reason: None,
+            replace_paths: Vec::new(),
+            values: HashMap::new(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TrowelDiffEntryBeforeAfter {
     before: TrowelDiffEntryBefore,
@@ -171,14 +271,61 @@ pub struct TrowelDiffEntryBeforeAfter {
 
 impl TrowelDiffEntryBeforeAfter {
     pub fn fmt(&self) -> Vec<Span<'_>> {
+        self.fmt_highlighted("")
+    }
+
+    pub fn before(&self) -> &TrowelDiffEntryBefore {
+        &self.before
+    }
+
+    pub fn after(&self) -> &TrowelDiffEntryAfter {
+        &self.after
+    }
+
+    /// Like `fmt`, but with every occurrence of the query in the values highlighted
+    pub fn fmt_highlighted(&self, query: &str) -> Vec<Span<'_>> {
         let before = Span::styled(Self::plaintext(&self.before), Self::style(&self.before));
 
         let after = Span::styled(Self::plaintext(&self.after), Self::style(&self.after));
 
-        vec![before, Span::from(" -> "), after]
+        let mut out = highlight(before, query);
+        out.push(Span::from(" -> "));
+        out.extend(highlight(after, query));
+        out
+    }
+
+    /// The value before the change as plain text, with sensitive values masked
+    pub fn before_plaintext(&self) -> String {
+        Self::plaintext(&self.before)
+    }
+
+    /// The value after the change as plain text, with sensitive values masked
+    pub fn after_plaintext(&self) -> String {
+        Self::plaintext(&self.after)
+    }
+
+    /// The value before the change, with JSON spread over several lines where it helps. Sensitive
+    /// values are masked.
+    pub fn before_pretty(&self) -> String {
+        Self::pretty(&self.before)
+    }
+
+    /// The value after the change, pretty-printed like `before_pretty`
+    pub fn after_pretty(&self) -> String {
+        Self::pretty(&self.after)
     }
 
-    fn changed(&self) -> bool {
+    /// How the value before the change is styled, which tells sensitive and unknown values apart
+    pub fn before_style(&self) -> Style {
+        Self::style(&self.before)
+    }
+
+    /// How the value after the change is styled
+    pub fn after_style(&self) -> Style {
+        Self::style(&self.after)
+    }
+
+    pub fn changed(&self) -> bool {
         match &self.before {
             TrowelDiffEntryBefore::Known(v1) => match &self.after {
                 TrowelDiffEntryBefore::Known(v2) => v1 != v2,
@@ -216,6 +363,15 @@ impl TrowelDiffEntryBeforeAfter {
         }
     }
 
+    fn pretty(v: &TrowelDiffEntryBefore) -> String {
+        match v {
+            TrowelDiffEntryBefore::Known(value) => {
+                serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+            }
+            _ => Self::plaintext(v),
+        }
+    }
+
     fn style(v: &TrowelDiffEntryBefore) -> Style {
         match v {
             TrowelDiffEntryBefore::Known(_) => Style::default(),
@@ -233,14 +389,14 @@ impl TrowelDiffEntryBeforeAfter {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-enum TrowelDiffEntryBefore {
+pub enum TrowelDiffEntryBefore {
     Known(Value),
     Sensitive(Value),
     Unknown,
     Absent,
 }
 
-type TrowelDiffEntryAfter = TrowelDiffEntryBefore;
+pub type TrowelDiffEntryAfter = TrowelDiffEntryBefore;
 
 fn get_before_value(
     attribute_name: &String,
@@ -249,15 +405,12 @@ fn get_before_value(
     let before_sensitive: Option<Value> = change
         .before_sensitive
         .inner()
-        .clone()
-        .map(|m| m.get(attribute_name).cloned())
-        .flatten()
-        .map(|v| v.clone());
+        .as_ref()
+        .and_then(|m| m.get(attribute_name).cloned());
     let before: Option<Value> = change
         .before
         .as_ref()
-        .and_then(|map| map.get(attribute_name).cloned())
-        .map(|v| v.clone());
+        .and_then(|map| map.get(attribute_name).cloned());
 
     match before_sensitive {
         Some(_) => match before {
@@ -284,15 +437,12 @@ fn get_after_value(
     let after_sensitive: Option<Value> = change
         .after_sensitive
         .inner()
-        .clone()
-        .map(|m| m.get(attribute_name).cloned())
-        .flatten()
-        .map(|v| v.clone());
+        .as_ref()
+        .and_then(|m| m.get(attribute_name).cloned());
     let after: Option<Value> = change
         .after
         .as_ref()
-        .and_then(|map| map.get(attribute_name).cloned())
-        .map(|v| v.clone());
+        .and_then(|map| map.get(attribute_name).cloned());
     let after_unknown: Option<TrowelDiffEntryAfter> = change
         .after_unknown
         .get(attribute_name)
@@ -365,17 +515,21 @@ mod tests {
         let plan = TfPlan {
             format_version: "".to_string(),
             terraform_version: "".to_string(),
+            variables: None,
             planned_values: TfPlanPlannedValues {
+                outputs: None,
                 root_module: TfPlanPlannedValuesRootModule {
                     resources: Some(vec![]),
                     child_modules: Some(vec![]),
                 },
             },
             resource_changes: Some(vec![]),
+            output_changes: None,
             prior_state: Some(TfPlanPriorState {
                 format_version: "".to_string(),
                 terraform_version: "".to_string(),
                 values: TfPlanPlannedValues {
+                    outputs: None,
                     root_module: TfPlanPlannedValuesRootModule {
                         resources: Some(vec![]),
                         child_modules: Some(vec![]),
@@ -403,7 +557,9 @@ mod tests {
         let plan = TfPlan {
             format_version: "".to_string(),
             terraform_version: "".to_string(),
+            variables: None,
             planned_values: TfPlanPlannedValues {
+                outputs: None,
                 root_module: TfPlanPlannedValuesRootModule {
                     resources: Some(vec![]),
                     child_modules: Some(vec![]),
@@ -429,10 +585,12 @@ mod tests {
                 module_address: None,
                 index: None,
             }]),
+            output_changes: None,
             prior_state: Some(TfPlanPriorState {
                 format_version: "".to_string(),
                 terraform_version: "".to_string(),
                 values: TfPlanPlannedValues {
+                    outputs: None,
                     root_module: TfPlanPlannedValuesRootModule {
                         resources: Some(vec![]),
                         child_modules: Some(vec![]),
@@ -458,6 +616,11 @@ mod tests {
             TrowelDiffEntry {
                 verb: Verb::Create,
                 resource_path: "apple".to_string(),
+                resource_type: "banana".to_string(),
+                provider: "guava".to_string(),
+                module: None,
+                action_reason: None,
+                replace_paths: Vec::new(),
                 values: HashMap::new()
             }
         )
@@ -466,18 +629,14 @@ mod tests {
     #[test]
     fn test_to_tree_items_empty() {
         let diff = TrowelDiff(vec![]);
-        let tree_items = diff.to_tree_items();
+        let tree_items = diff.to_tree_items("", &TrowelFilter::default());
         assert_eq!(tree_items.unwrap().len(), 0);
     }
 
     #[test]
     fn test_to_tree_items_one_empty() {
-        let diff = TrowelDiff(vec![TrowelDiffEntry {
-            verb: Verb::Create,
-            resource_path: "apple".to_string(),
-            values: HashMap::new(),
-        }]);
-        let tree_items = diff.to_tree_items().unwrap();
+        let diff = TrowelDiff(vec![TrowelDiffEntry::test("apple", Verb::Create)]);
+        let tree_items = diff.to_tree_items("", &TrowelFilter::default()).unwrap();
         assert_eq!(tree_items.len(), 1);
         let item = &tree_items[0];
         assert_eq!(item.identifier(), "apple");
@@ -487,8 +646,6 @@ mod tests {
     #[test]
     fn test_to_tree_items_one_empty_nonempty() {
         let diff = TrowelDiff(vec![TrowelDiffEntry {
-            verb: Verb::Create,
-            resource_path: "apple".to_string(),
             values: HashMap::from([
                 (
                     "c".to_string(),
@@ -526,8 +683,9 @@ mod tests {
                     },
                 ),
             ]),
+            ..TrowelDiffEntry::test("apple", Verb::Create)
         }]);
-        let tree_items = diff.to_tree_items().unwrap();
+        let tree_items = diff.to_tree_items("", &TrowelFilter::default()).unwrap();
         assert_eq!(tree_items.len(), 1);
         let item = &tree_items[0];
         assert_eq!(item.identifier(), "apple");
@@ -541,26 +699,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_search() {
+        let diff = TrowelDiff(vec![TrowelDiffEntry {
+            values: HashMap::from([
+                (
+                    "length".to_string(),
+                    TrowelDiffEntryBeforeAfter {
+                        before: TrowelDiffEntryBefore::Known(Value::from(2)),
+                        after: TrowelDiffEntryBefore::Known(Value::from(3)),
+                    },
+                ),
+                (
+                    "keepers".to_string(),
+                    TrowelDiffEntryBeforeAfter {
+                        before: TrowelDiffEntryBefore::Sensitive(Value::from("hunter2")),
+                        after: TrowelDiffEntryBefore::Sensitive(Value::from("hunter3")),
+                    },
+                ),
+                (
+                    "prefix".to_string(),
+                    TrowelDiffEntryBeforeAfter {
+                        before: TrowelDiffEntryBefore::Known(Value::from("apple")),
+                        after: TrowelDiffEntryBefore::Known(Value::from("apple")),
+                    },
+                ),
+            ]),
+            ..TrowelDiffEntry::test("apple", Verb::Update)
+        }]);
+        let matches = |query: &str| -> Vec<String> {
+            diff.search(query, &TrowelFilter::default())
+                .into_iter()
+                .filter(|(_, matches)| *matches)
+                .filter_map(|(path, _)| path.last().cloned())
+                .collect()
+        };
+
+        // Nodes are listed in the same order as the tree
+        let paths: Vec<_> = diff
+            .search("", &TrowelFilter::default())
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        let expected: Vec<Vec<String>> = vec![
+            vec!["apple".into()],
+            vec!["apple".into(), "apple keepers".into()],
+            vec!["apple".into(), "apple length".into()],
+            vec!["apple".into(), "apple unchanged".into()],
+        ];
+        assert_eq!(paths, expected);
+
+        assert_eq!(matches("APPLE"), vec!["apple"]);
+        assert_eq!(matches("len"), vec!["apple length"]);
+        assert_eq!(matches("3"), vec!["apple length"]);
+        // Sensitive values can't be found
+        assert!(matches("hunter").is_empty());
+        assert!(matches("").is_empty());
+    }
+
     #[test]
     fn test_to_tree_items_multiple_empty() {
         let diff = TrowelDiff(vec![
-            TrowelDiffEntry {
-                verb: Verb::Create,
-                resource_path: "orange".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Update,
-                resource_path: "banana".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Destroy,
-                resource_path: "apple".to_string(),
-                values: HashMap::new(),
-            },
+            TrowelDiffEntry::test("orange", Verb::Create),
+            TrowelDiffEntry::test("banana", Verb::Update),
+            TrowelDiffEntry::test("apple", Verb::Destroy),
         ]);
-        let tree_items = diff.to_tree_items().unwrap();
+        let tree_items = diff.to_tree_items("", &TrowelFilter::default()).unwrap();
         assert_eq!(tree_items.len(), 3);
         let identifiers: Vec<_> = tree_items.iter().map(|i| i.identifier()).collect();
         assert_eq!(identifiers, vec!["orange", "banana", "apple"]) // Does not alphabetize resources
@@ -575,11 +779,7 @@ mod tests {
 
     #[test]
     fn test_verb_uses_one() {
-        let diff = TrowelDiff(vec![TrowelDiffEntry {
-            verb: Verb::Create,
-            resource_path: "foo".to_string(),
-            values: HashMap::new(),
-        }]);
+        let diff = TrowelDiff(vec![TrowelDiffEntry::test("foo", Verb::Create)]);
 
         let uses = diff.verb_uses();
         assert_eq!(uses, [(Verb::Create, 1)].into_iter().collect())
@@ -588,21 +788,9 @@ mod tests {
     #[test]
     fn test_verb_uses_multiple() {
         let diff = TrowelDiff(vec![
-            TrowelDiffEntry {
-                verb: Verb::Create,
-                resource_path: "foo".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Update,
-                resource_path: "bar".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Create,
-                resource_path: "baz".to_string(),
-                values: HashMap::new(),
-            },
+            TrowelDiffEntry::test("foo", Verb::Create),
+            TrowelDiffEntry::test("bar", Verb::Update),
+            TrowelDiffEntry::test("baz", Verb::Create),
         ]);
 
         let uses = diff.verb_uses();
@@ -612,6 +800,30 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_verb_uses_many() {
+        let diff = TrowelDiff(
+            (0..400)
+                .map(|i| TrowelDiffEntry::test(&format!("pet{}", i), Verb::Create))
+                .collect(),
+        );
+
+        let uses = diff.verb_uses();
+        assert_eq!(uses, [(Verb::Create, 400)].into_iter().collect())
+    }
+
+    #[test]
+    fn test_verb_uses_sorted() {
+        let diff = TrowelDiff(vec![
+            TrowelDiffEntry::test("foo", Verb::Update),
+            TrowelDiffEntry::test("bar", Verb::Create),
+            TrowelDiffEntry::test("baz", Verb::Update),
+        ]);
+
+        let uses = diff.verb_uses_sorted();
+        assert_eq!(uses, vec![(Verb::Create, 1), (Verb::Update, 2)]);
+    }
+
     #[test]
     fn test_verb_uses_fmt_empty() {
         let diff = TrowelDiff(vec![]);
@@ -621,11 +833,7 @@ mod tests {
 
     #[test]
     fn test_verb_uses_fmt_one() {
-        let diff = TrowelDiff(vec![TrowelDiffEntry {
-            verb: Verb::Create,
-            resource_path: "foo".to_string(),
-            values: HashMap::new(),
-        }]);
+        let diff = TrowelDiff(vec![TrowelDiffEntry::test("foo", Verb::Create)]);
         let uses = diff.verb_uses_fmt();
         assert_eq!(
             uses,
@@ -645,26 +853,10 @@ mod tests {
     #[test]
     fn test_verb_uses_fmt_multiple() {
         let diff = TrowelDiff(vec![
-            TrowelDiffEntry {
-                verb: Verb::Destroy,
-                resource_path: "apple".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Update,
-                resource_path: "banana".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Create,
-                resource_path: "orange".to_string(),
-                values: HashMap::new(),
-            },
-            TrowelDiffEntry {
-                verb: Verb::Destroy,
-                resource_path: "mango".to_string(),
-                values: HashMap::new(),
-            },
+            TrowelDiffEntry::test("apple", Verb::Destroy),
+            TrowelDiffEntry::test("banana", Verb::Update),
+            TrowelDiffEntry::test("orange", Verb::Create),
+            TrowelDiffEntry::test("mango", Verb::Destroy),
         ]);
         let uses = diff.verb_uses_fmt();
         // Entries will be sorted by the numeric value of each Verb
@@ -695,6 +887,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_filtered_out_entries() {
+        let diff = TrowelDiff(vec![
+            TrowelDiffEntry::test("apple", Verb::Update),
+            TrowelDiffEntry::test("banana", Verb::Destroy),
+        ]);
+        let mut filter = TrowelFilter::default();
+        filter.toggle_verb(&Verb::Destroy);
+
+        // Filtered out entries are left out of the tree and searches
+        let tree_items = diff.to_tree_items("", &filter).unwrap();
+        assert_eq!(tree_items.len(), 1);
+        assert_eq!(tree_items[0].identifier(), "banana");
+        assert_eq!(
+            diff.search("apple", &filter),
+            vec![(vec!["banana".to_string()], false)]
+        );
+    }
+
     #[test]
     fn test_get_before_value() {
         let change = TfPlanResourceChangeChange {
@@ -0,0 +1,251 @@
+use std::collections::BTreeSet;
+
+use super::{
+    text_search::{contains_match, glob_matches},
+    trowel_diff::TrowelDiffEntry,
+    verb::Verb,
+};
+
+/// Which entries of a diff are shown
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrowelFilter {
+    /// Only entries with these verbs are shown, or every entry when empty
+    pub verbs: BTreeSet<Verb>,
+    /// Only entries matching every term are shown
+    pub terms: Vec<TrowelFilterTerm>,
+    /// The expression the terms were parsed from, as it was typed
+    pub expression: String,
+}
+
+impl TrowelFilter {
+    /// Verbs in the order they're summarised in
+    pub const VERBS: [Verb; 6] = [
+        Verb::Create,
+        Verb::Destroy,
+        Verb::Replace,
+        Verb::Update,
+        Verb::Read,
+        Verb::Forget,
+    ];
+
+    pub fn is_active(&self) -> bool {
+        !self.verbs.is_empty() || !self.terms.is_empty()
+    }
+
+    pub fn matches(&self, entry: &TrowelDiffEntry) -> bool {
+        (self.verbs.is_empty() || self.verbs.contains(&entry.verb))
+            && self.terms.iter().all(|t| t.matches(entry))
+    }
+
+    /// Replaces the terms with those of an expression such as `type:aws_iam_* !verb:update`.
+    /// Terms are separated by spaces, and every term must match for an entry to be shown.
+    pub fn set_expression(&mut self, expression: &str) -> Result<(), String> {
+        self.terms = expression
+            .split_whitespace()
+            .map(TrowelFilterTerm::parse)
+            .collect::<Result<_, _>>()?;
+        self.expression = expression.trim().to_string();
+        Ok(())
+    }
+
+    /// Adds the verb to those shown, or removes it if it's already shown
+    pub fn toggle_verb(&mut self, verb: &Verb) {
+        if !self.verbs.remove(verb) {
+            self.verbs.insert(verb.clone());
+        }
+    }
+}
+
+/// One condition on an entry, e.g. `type:aws_iam_*`. A term may list several patterns
+/// separated by commas, any of which can match, and is negated by starting it with `!` or `-`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrowelFilterTerm {
+    pub field: TrowelFilterField,
+    pub patterns: Vec<String>,
+    pub negated: bool,
+}
+
+impl TrowelFilterTerm {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (negated, term) = match text.strip_prefix(['!', '-']) {
+            Some(term) => (true, term),
+            None => (false, text),
+        };
+        let (field, value) = match term.split_once(':') {
+            Some((key, value)) => match TrowelFilterField::from_key(key) {
+                Some(field) => (field, value),
+                None => return Err(format!("Unknown filter \"{}:\"", key)),
+            },
+            None => (TrowelFilterField::Address, term),
+        };
+        let patterns: Vec<String> = value
+            .split(',')
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect();
+        if patterns.is_empty() {
+            return Err(format!("\"{}\" needs a value", text));
+        }
+        if field == TrowelFilterField::Verb
+            && let Some(unknown) = patterns
+                .iter()
+                .find(|p| !TrowelFilter::VERBS.iter().any(|v| v.name_lower() == **p))
+        {
+            return Err(format!("Unknown verb \"{}\"", unknown));
+        }
+        Ok(Self {
+            field,
+            patterns,
+            negated,
+        })
+    }
+
+    fn matches(&self, entry: &TrowelDiffEntry) -> bool {
+        let matches = self.patterns.iter().any(|p| self.field.matches(p, entry));
+        matches != self.negated
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrowelFilterField {
+    /// Part of the address, given without a key
+    Address,
+    /// The resource type, e.g. `type:aws_iam_*`
+    Type,
+    /// The address of the containing module or the name of any module in it, e.g.
+    /// `module:network`. Resources in the root module never match.
+    Module,
+    /// The name of any attribute, e.g. `attr:tags`
+    Attr,
+    /// The name of any changed attribute, e.g. `changed:policy`
+    Changed,
+    /// The action, e.g. `verb:replace`
+    Verb,
+}
+
+impl TrowelFilterField {
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "type" => Some(Self::Type),
+            "module" => Some(Self::Module),
+            "attr" => Some(Self::Attr),
+            "changed" => Some(Self::Changed),
+            "verb" => Some(Self::Verb),
+            _ => None,
+        }
+    }
+
+    /// Whether the entry matches a pattern, in which `*` matches any run of characters.
+    /// Addresses are instead searched for the pattern, ignoring case.
+    fn matches(&self, pattern: &str, entry: &TrowelDiffEntry) -> bool {
+        match self {
+            Self::Address => contains_match(&entry.resource_path, pattern),
+            Self::Type => glob_matches(pattern, &entry.resource_type),
+            Self::Module => entry.module.as_deref().is_some_and(|module| {
+                glob_matches(pattern, module)
+                    || module
+                        .split("module.")
+                        .map(|name| name.trim_end_matches('.'))
+                        .any(|name| !name.is_empty() && glob_matches(pattern, name))
+            }),
+            Self::Attr => entry.values.keys().any(|k| glob_matches(pattern, k)),
+            Self::Changed => entry
+                .values
+                .iter()
+                .any(|(k, v)| v.changed() && glob_matches(pattern, k)),
+            Self::Verb => entry.verb.name_lower() == pattern,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::model::{tf_plan::TfPlan, trowel_diff::TrowelDiff};
+
+    use super::*;
+
+    #[test]
+    fn test_toggle_verb() {
+        let mut filter = TrowelFilter::default();
+        filter.toggle_verb(&Verb::Destroy);
+        filter.toggle_verb(&Verb::Replace);
+        assert_eq!(filter.verbs, BTreeSet::from([Verb::Destroy, Verb::Replace]));
+        filter.toggle_verb(&Verb::Destroy);
+        filter.toggle_verb(&Verb::Replace);
+        assert!(!filter.is_active());
+    }
+
+    #[test]
+    fn test_set_expression() {
+        let diff = TrowelDiff::test(vec![
+            change("aws_iam_role.admin", "update", "policy"),
+            change("aws_iam_role.reader", "update", "tags"),
+            change("module.network.aws_vpc.main", "delete", "tags"),
+        ]);
+        let shown = |expression: &str| -> Vec<String> {
+            let mut filter = TrowelFilter::default();
+            filter.set_expression(expression).unwrap();
+            diff.entries()
+                .iter()
+                .filter(|e| filter.matches(e))
+                .map(|e| e.resource_path.clone())
+                .collect()
+        };
+
+        assert_eq!(shown("").len(), 3);
+        assert_eq!(shown("type:aws_iam_*").len(), 2);
+        assert_eq!(shown("module:network"), vec!["module.network.aws_vpc.main"]);
+        assert_eq!(shown("module:module.net*").len(), 1);
+        assert_eq!(shown("changed:policy"), vec!["aws_iam_role.admin"]);
+        // Every resource has tags, but only some change them
+        assert_eq!(shown("attr:tags").len(), 3);
+        assert_eq!(shown("changed:tags").len(), 2);
+        assert_eq!(shown("verb:destroy,replace").len(), 1);
+        assert_eq!(
+            shown("type:aws_iam_* !changed:policy"),
+            vec!["aws_iam_role.reader"]
+        );
+        assert_eq!(
+            shown("-READER"),
+            vec!["aws_iam_role.admin", "module.network.aws_vpc.main"]
+        );
+    }
+
+    #[test]
+    fn test_set_expression_errors() {
+        let mut filter = TrowelFilter::default();
+        filter.set_expression("verb:destroy").unwrap();
+        assert_eq!(
+            filter.set_expression("colour:red"),
+            Err("Unknown filter \"colour:\"".to_string())
+        );
+        assert_eq!(
+            filter.set_expression("verb:obliterate"),
+            Err("Unknown verb \"obliterate\"".to_string())
+        );
+        assert_eq!(
+            filter.set_expression("!type:"),
+            Err("\"!type:\" needs a value".to_string())
+        );
+        // A bad expression leaves the filter as it was
+        assert_eq!(filter.expression, "verb:destroy");
+        assert_eq!(filter.terms.len(), 1);
+    }
+
+    /// A resource change that changes one attribute and leaves `tags` alone unless that's the
+    /// attribute being changed
+    fn change(address: &str, action: &str, attribute: &str) -> Value {
+        let before = json!({"tags": "old", "name": "old"});
+        let mut after = before.clone();
+        after[attribute] = json!("new");
+        if action == "delete" {
+            after = Value::Null;
+        }
+        let mut change = TfPlan::test_change(address, action);
+        change["change"]["before"] = before;
+        change["change"]["after"] = after;
+        change
+    }
+}
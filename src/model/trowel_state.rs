@@ -0,0 +1,337 @@
+use std::io;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use serde_json::Value;
+use tui_tree_widget::TreeItem;
+
+use super::tf_plan::TfPlanPlannedValuesModuleResource;
+use super::tf_state::{TfState, TfStateFile, TfStateValues};
+
+/// Every resource instance and output in a state, flattened so that it can be browsed and searched
+pub struct TrowelState(Vec<TrowelStateEntry>);
+
+impl TrowelState {
+    pub fn from_tf_state(state: &TfState) -> Self {
+        let mut out = TrowelState(Vec::new());
+        if let Some(values) = &state.values {
+            out.push_values(values);
+        }
+        out.sort();
+        out
+    }
+
+    pub fn from_state_file(state: &TfStateFile) -> Result<Self, io::Error> {
+        if state.version != TfStateFile::SUPPORTED_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "State file version {} is not supported, expected version {}",
+                    state.version,
+                    TfStateFile::SUPPORTED_VERSION
+                ),
+            ));
+        }
+
+        let mut out = TrowelState(Vec::new());
+        for resource in &state.resources {
+            for instance in &resource.instances {
+                let sensitive = instance.sensitive_attribute_names();
+                let attributes = instance
+                    .attributes
+                    .iter()
+                    .flatten()
+                    .map(|(k, v)| {
+                        let value = if sensitive.contains(k) {
+                            TrowelStateValue::Sensitive
+                        } else {
+                            TrowelStateValue::Known(v.clone())
+                        };
+                        (k.clone(), value)
+                    })
+                    .collect();
+                out.0.push(TrowelStateEntry {
+                    address: resource.instance_address(instance),
+                    status: match (&instance.deposed, instance.status.as_deref()) {
+                        (Some(key), _) => Some(format!("deposed object {}", key)),
+                        (None, Some("tainted")) => Some("tainted".to_string()),
+                        _ => None,
+                    },
+                    attributes,
+                });
+            }
+        }
+        for (name, output) in &state.outputs {
+            out.0.push(TrowelStateEntry::output(
+                name,
+                output.sensitive,
+                Some(&output.value),
+            ));
+        }
+        out.sort();
+        Ok(out)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Builds tree items for the entries matching the query, which is ignored when empty
+    pub fn to_tree_items(&self, query: &str) -> Result<Vec<TreeItem<'_, String>>, io::Error> {
+        let mut out = vec![];
+
+        for e in self.0.iter().filter(|e| e.matches(query)) {
+            let identifier = e.identifier();
+            let attributes = e
+                .attributes
+                .iter()
+                .map(|(k, v)| {
+                    TreeItem::new_leaf(
+                        format!("{} {}", identifier, k),
+                        Line::from(vec![Span::from(k.as_str()), Span::from(" "), v.fmt()]),
+                    )
+                })
+                .collect();
+
+            let mut line = vec![Span::styled(
+                e.address.as_str(),
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            )];
+            if let Some(status) = &e.status {
+                line.push(Span::styled(
+                    format!(" ({})", status),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
+
+            out.push(TreeItem::new(identifier, Line::from(line), attributes)?);
+        }
+
+        Ok(out)
+    }
+
+    fn push_values(&mut self, values: &TfStateValues) {
+        let mut resources: Vec<&TfPlanPlannedValuesModuleResource> = Vec::new();
+        resources.extend(values.root_module.resources.iter().flatten());
+        let mut modules: Vec<_> = values.root_module.child_modules.iter().flatten().collect();
+        while let Some(module) = modules.pop() {
+            resources.extend(module.resources.iter().flatten());
+            modules.extend(module.child_modules.iter().flatten());
+        }
+
+        for resource in resources {
+            let attributes = resource
+                .values
+                .iter()
+                .map(|(k, v)| {
+                    let sensitive = resource
+                        .sensitive_values
+                        .get(k)
+                        .is_some_and(contains_sensitive);
+                    let value = if sensitive {
+                        TrowelStateValue::Sensitive
+                    } else {
+                        TrowelStateValue::Known(v.clone())
+                    };
+                    (k.clone(), value)
+                })
+                .collect();
+            let status = match (&resource.deposed_key, resource.tainted) {
+                (Some(key), _) => Some(format!("deposed object {}", key)),
+                (None, Some(true)) => Some("tainted".to_string()),
+                _ => None,
+            };
+            self.0.push(TrowelStateEntry {
+                address: resource.address.clone(),
+                status,
+                attributes,
+            });
+        }
+
+        for (name, output) in values.outputs.iter().flatten() {
+            self.0.push(TrowelStateEntry::output(
+                name,
+                output.sensitive,
+                output.value.as_ref(),
+            ));
+        }
+    }
+
+    fn sort(&mut self) {
+        for e in &mut self.0 {
+            e.attributes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        // Deposed objects share an address with the current object, so status breaks ties
+        self.0
+            .sort_by(|a, b| (&a.address, &a.status).cmp(&(&b.address, &b.status)));
+    }
+}
+
+struct TrowelStateEntry {
+    address: String,
+    /// Set for objects that aren't the healthy current object, e.g. tainted or deposed ones
+    status: Option<String>,
+    attributes: Vec<(String, TrowelStateValue)>,
+}
+
+impl TrowelStateEntry {
+    fn output(name: &str, sensitive: bool, value: Option<&Value>) -> Self {
+        let value = match (sensitive, value) {
+            (true, _) => TrowelStateValue::Sensitive,
+            (false, Some(v)) => TrowelStateValue::Known(v.clone()),
+            (false, None) => TrowelStateValue::Known(Value::Null),
+        };
+        Self {
+            address: format!("output.{}", name),
+            status: None,
+            attributes: vec![("value".to_string(), value)],
+        }
+    }
+
+    /// Deposed objects share an address with the current object, so the status tells them apart
+    fn identifier(&self) -> String {
+        match &self.status {
+            Some(status) => format!("{} ({})", self.address, status),
+            None => self.address.clone(),
+        }
+    }
+
+    /// Case-insensitive search of the address, attribute names and values. Sensitive values are
+    /// never searched, so that a search can't reveal anything about them.
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        let contains = |s: &str| s.to_lowercase().contains(&query);
+        contains(&self.address)
+            || self.attributes.iter().any(|(k, v)| {
+                contains(k)
+                    || match v {
+                        TrowelStateValue::Known(value) => contains(&value.to_string()),
+                        TrowelStateValue::Sensitive => false,
+                    }
+            })
+    }
+}
+
+enum TrowelStateValue {
+    Known(Value),
+    Sensitive,
+}
+
+impl TrowelStateValue {
+    fn fmt(&self) -> Span<'static> {
+        match self {
+            Self::Known(value) => Span::from(value.to_string()),
+            Self::Sensitive => Span::styled(
+                "(sensitive value)",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        }
+    }
+}
+
+/// Sensitive values mirror the structure of the values, with `true` wherever a value is sensitive
+fn contains_sensitive(sensitive_value: &Value) -> bool {
+    match sensitive_value {
+        Value::Bool(b) => *b,
+        Value::Array(values) => values.iter().any(contains_sensitive),
+        Value::Object(map) => map.values().any(contains_sensitive),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE: &str = r#"{
+        "format_version": "1.0",
+        "terraform_version": "1.9.0",
+        "values": {
+            "outputs": {
+                "password": {"sensitive": true, "value": "hunter2", "type": "string"}
+            },
+            "root_module": {
+                "resources": [
+                    {
+                        "address": "random_pet.apple",
+                        "mode": "managed",
+                        "type": "random_pet",
+                        "name": "apple",
+                        "provider_name": "registry.opentofu.org/hashicorp/random",
+                        "schema_version": 0,
+                        "values": {"id": "fond-koala", "keepers": {"colour": "red"}},
+                        "sensitive_values": {"keepers": {}}
+                    }
+                ],
+                "child_modules": [
+                    {
+                        "address": "module.fruit",
+                        "resources": [
+                            {
+                                "address": "module.fruit.random_password.kiwi",
+                                "mode": "managed",
+                                "type": "random_password",
+                                "name": "kiwi",
+                                "provider_name": "registry.opentofu.org/hashicorp/random",
+                                "schema_version": 3,
+                                "values": {"length": 16, "result": "s3cret"},
+                                "sensitive_values": {"result": true},
+                                "tainted": true
+                            }
+                        ]
+                    }
+                ]
+            }
+        }
+    }"#;
+
+    fn state() -> TrowelState {
+        TrowelState::from_tf_state(&serde_json::from_str(STATE).unwrap())
+    }
+
+    #[test]
+    fn test_from_tf_state() {
+        let state = state();
+        let addresses: Vec<_> = state.0.iter().map(|e| e.address.as_str()).collect();
+        assert_eq!(
+            addresses,
+            vec![
+                "module.fruit.random_password.kiwi",
+                "output.password",
+                "random_pet.apple"
+            ]
+        );
+        assert_eq!(state.0[0].status.as_deref(), Some("tainted"));
+        assert!(matches!(
+            state.0[0].attributes[1],
+            (_, TrowelStateValue::Sensitive)
+        ));
+        // Sensitive values that contain nothing sensitive are shown
+        assert!(matches!(
+            state.0[2].attributes[1],
+            (_, TrowelStateValue::Known(_))
+        ));
+    }
+
+    #[test]
+    fn test_matches() {
+        let state = state();
+        let matching = |query: &str| state.0.iter().filter(|e| e.matches(query)).count();
+        assert_eq!(matching(""), 3);
+        assert_eq!(matching("APPLE"), 1);
+        assert_eq!(matching("koala"), 1);
+        assert_eq!(matching("length"), 1);
+        assert_eq!(matching("hunter2"), 0);
+        assert_eq!(matching("s3cret"), 0);
+    }
+}
@@ -1,15 +1,18 @@
 use ratatui::style::Color;
+use serde::Deserialize;
 use std::io;
 
 use super::tf_plan::TfPlanResourceChange;
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Verb {
     Create = 0,
     Update = 3,
     Replace = 2,
     Destroy = 1,
     Read = 4,
+    Forget = 5,
     Ignore = 99,
 }
 
@@ -29,6 +32,8 @@ impl Verb {
             Ok(Self::Replace)
         } else if sorted == vec!["read"] {
             Ok(Self::Read)
+        } else if sorted == vec!["forget"] {
+            Ok(Self::Forget)
         } else {
             Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -37,6 +42,20 @@ impl Verb {
         }
     }
 
+    /// Maps an action from a machine-readable `planned_change` event onto a Verb
+    pub fn from_event_action(action: &str) -> Option<Self> {
+        match action {
+            "noop" => Some(Self::Ignore),
+            "create" => Some(Self::Create),
+            "update" => Some(Self::Update),
+            "delete" => Some(Self::Destroy),
+            "replace" => Some(Self::Replace),
+            "read" => Some(Self::Read),
+            "forget" => Some(Self::Forget),
+            _ => None,
+        }
+    }
+
     pub fn to_past_tense(&self) -> String {
         match self {
             Self::Create => "created",
@@ -44,6 +63,7 @@ impl Verb {
             Self::Replace => "replaced",
             Self::Destroy => "destroyed",
             Self::Read => "read",
+            Self::Forget => "forgotten",
             Self::Ignore => "ignored",
         }
         .to_string()
@@ -56,6 +76,7 @@ impl Verb {
             Self::Replace => Color::Magenta,
             Self::Destroy => Color::Red,
             Self::Read => Color::Cyan,
+            Self::Forget => Color::Blue,
             Self::Ignore => Color::Gray,
         }
     }
@@ -67,6 +88,7 @@ impl Verb {
             Verb::Replace => "Replace",
             Verb::Destroy => "Destroy",
             Verb::Read => "Read",
+            Verb::Forget => "Forget",
             Verb::Ignore => "Ignore",
         }
     }
@@ -132,5 +154,20 @@ mod tests {
 
         actions.change.actions = vec!["read".to_string()];
         assert_eq!(Verb::from_resource(&actions).unwrap(), Verb::Read);
+
+        actions.change.actions = vec!["forget".to_string()];
+        assert_eq!(Verb::from_resource(&actions).unwrap(), Verb::Forget);
+    }
+
+    #[test]
+    fn test_from_event_action() {
+        assert_eq!(Verb::from_event_action("noop"), Some(Verb::Ignore));
+        assert_eq!(Verb::from_event_action("create"), Some(Verb::Create));
+        assert_eq!(Verb::from_event_action("update"), Some(Verb::Update));
+        assert_eq!(Verb::from_event_action("delete"), Some(Verb::Destroy));
+        assert_eq!(Verb::from_event_action("replace"), Some(Verb::Replace));
+        assert_eq!(Verb::from_event_action("read"), Some(Verb::Read));
+        assert_eq!(Verb::from_event_action("forget"), Some(Verb::Forget));
+        assert_eq!(Verb::from_event_action("move"), None);
     }
 }
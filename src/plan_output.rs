@@ -0,0 +1,137 @@
+use std::{
+    env,
+    fs::{self, DirBuilder, Permissions},
+    io,
+    os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use nix::unistd::Uid;
+use tempfile::NamedTempFile;
+
+/// Where a binary plan is written while trowel runs
+pub enum PlanOutput {
+    /// Deleted once the plan has been shown
+    Temporary(NamedTempFile),
+    /// Written beside the path given to `--save-plan`, replacing it only once planning succeeds
+    Pending {
+        file: NamedTempFile,
+        target: PathBuf,
+    },
+    /// Kept after trowel exits so that the reviewed plan can be applied
+    Saved(PathBuf),
+}
+
+impl PlanOutput {
+    const DIR_MODE: u32 = 0o700;
+    const FILE_MODE: u32 = 0o600;
+
+    pub fn new(save_path: Option<PathBuf>) -> Result<Self, io::Error> {
+        match save_path {
+            Some(target) => {
+                // A plan already at the path is left alone until there's a new one to replace it.
+                // The file is created private, so the plan is never written with broader permissions.
+                let dir = match target.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => dir,
+                    _ => Path::new("."),
+                };
+                let file = tempfile::Builder::new()
+                    .prefix(".trowel-plan-")
+                    .permissions(Permissions::from_mode(Self::FILE_MODE))
+                    .tempfile_in(dir)?;
+                Ok(Self::Pending { file, target })
+            }
+            None => {
+                let tempfile = tempfile::Builder::new()
+                    .prefix("plan-")
+                    .permissions(Permissions::from_mode(Self::FILE_MODE))
+                    .tempfile_in(Self::secure_dir()?)?;
+                Ok(Self::Temporary(tempfile))
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Temporary(tempfile) => tempfile.path(),
+            Self::Pending { file, .. } => file.path(),
+            Self::Saved(path) => path,
+        }
+    }
+
+    /// Moves a successful plan to the path it's being saved to
+    pub fn keep(self) -> Result<Self, io::Error> {
+        match self {
+            Self::Pending { file, target } => {
+                file.persist(&target).map_err(|e| e.error)?;
+                Ok(Self::Saved(target))
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// Removes the plan, e.g. when planning failed and there is nothing worth keeping. Saved plans
+    /// are left in place, as is anything that was at their path before.
+    pub fn discard(self) -> Result<(), io::Error> {
+        match self {
+            Self::Temporary(file) | Self::Pending { file, .. } => file.close(),
+            Self::Saved(_) => Ok(()),
+        }
+    }
+
+    /// A directory only the current user can access, preferring the per-user runtime directory
+    fn secure_dir() -> Result<PathBuf, io::Error> {
+        let uid = Uid::current();
+        let dir = match env::var_os("XDG_RUNTIME_DIR") {
+            Some(runtime_dir) => PathBuf::from(runtime_dir).join("trowel"),
+            None => env::temp_dir().join(format!("trowel-{}", uid)),
+        };
+        DirBuilder::new()
+            .recursive(true)
+            .mode(Self::DIR_MODE)
+            .create(&dir)?;
+
+        // The directory may already exist, e.g. created by another user in a shared temp dir
+        let metadata = fs::symlink_metadata(&dir)?;
+        if !metadata.is_dir() || metadata.uid() != uid.as_raw() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "{} is not a directory owned by the current user",
+                    dir.display()
+                ),
+            ));
+        }
+        if metadata.mode() & 0o077 != 0 {
+            fs::set_permissions(&dir, Permissions::from_mode(Self::DIR_MODE))?;
+        }
+        Ok(dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_plan_replaces_target_only_when_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("reviewed.tfplan");
+        fs::write(&target, "old plan").unwrap();
+
+        let output = PlanOutput::new(Some(target.clone())).unwrap();
+        fs::write(output.path(), "failed plan").unwrap();
+        output.discard().unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old plan");
+
+        let output = PlanOutput::new(Some(target.clone())).unwrap();
+        assert_ne!(output.path(), target);
+        fs::write(output.path(), "new plan").unwrap();
+        let output = output.keep().unwrap();
+        assert_eq!(output.path(), target);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new plan");
+        let mode = fs::metadata(&target).unwrap().mode() & 0o777;
+        assert_eq!(mode, PlanOutput::FILE_MODE);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
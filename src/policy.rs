@@ -0,0 +1,169 @@
+use std::{fs, io, path::Path};
+
+use serde::Deserialize;
+
+use crate::model::{
+    text_search::glob_matches,
+    trowel_diff::{TrowelDiff, TrowelDiffEntry},
+    verb::Verb,
+};
+
+/// Rules that a plan must follow, read from a TOML file such as:
+///
+/// ```toml
+/// max_changes = 20
+///
+/// [[deny]]
+/// name = "no database destroys"
+/// verbs = ["destroy", "replace"]
+/// resource_type = "aws_db_*"
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// The most resources the plan may change. Data sources being read don't count.
+    pub max_changes: Option<usize>,
+    #[serde(default)]
+    pub deny: Vec<DenyRule>,
+}
+
+/// Denies changes to the resources matching every field that is set. Patterns may use `*` to
+/// match any run of characters.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DenyRule {
+    pub name: Option<String>,
+    /// The actions denied, or every action when empty
+    #[serde(default)]
+    pub verbs: Vec<Verb>,
+    pub resource_type: Option<String>,
+    /// Resources in the root module never match a module pattern
+    pub module: Option<String>,
+    pub address: Option<String>,
+}
+
+impl Policy {
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid policy file {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.deny.len() + usize::from(self.max_changes.is_some())
+    }
+
+    /// Describes every way in which the plan breaks the rules
+    pub fn violations(&self, diff: &TrowelDiff) -> Vec<String> {
+        let mut out = Vec::new();
+
+        for (i, rule) in self.deny.iter().enumerate() {
+            let name = match &rule.name {
+                Some(name) => format!("\"{}\"", name),
+                None => format!("#{}", i + 1),
+            };
+            for e in diff.entries().iter().filter(|e| rule.denies(e)) {
+                out.push(format!(
+                    "{} will be {}, which is denied by rule {}",
+                    e.resource_path,
+                    e.verb.to_past_tense(),
+                    name
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_changes {
+            let changes = diff
+                .entries()
+                .iter()
+                .filter(|e| e.verb != Verb::Read)
+                .count();
+            if changes > max {
+                out.push(format!(
+                    "{} resources will be changed, but at most {} may be",
+                    changes, max
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+impl DenyRule {
+    fn denies(&self, entry: &TrowelDiffEntry) -> bool {
+        let matches = |pattern: &Option<String>, text: Option<&str>| match pattern {
+            Some(pattern) => text.is_some_and(|t| glob_matches(pattern, t)),
+            None => true,
+        };
+        (self.verbs.is_empty() || self.verbs.contains(&entry.verb))
+            && matches(&self.resource_type, Some(&entry.resource_type))
+            && matches(&self.module, entry.module.as_deref())
+            && matches(&self.address, Some(&entry.resource_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::tf_plan::TfPlan;
+
+    use super::*;
+
+    /// A plan containing the given resource changes, as `(address, action)`
+    fn diff(changes: &[(&str, &str)]) -> TrowelDiff {
+        TrowelDiff::test(
+            changes
+                .iter()
+                .map(|(address, action)| TfPlan::test_change(address, action))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_violations() {
+        let policy: Policy = toml::from_str(
+            r#"
+            max_changes = 2
+
+            [[deny]]
+            name = "no database destroys"
+            verbs = ["destroy"]
+            resource_type = "aws_db_*"
+
+            [[deny]]
+            module = "module.network*"
+            "#,
+        )
+        .unwrap();
+        let diff = diff(&[
+            ("aws_db_instance.main", "delete"),
+            ("aws_db_subnet_group.main", "create"),
+            ("module.network.aws_vpc.main", "update"),
+        ]);
+        assert_eq!(
+            policy.violations(&diff),
+            vec![
+                "aws_db_instance.main will be destroyed, which is denied by rule \"no database destroys\"",
+                "module.network.aws_vpc.main will be updated, which is denied by rule #2",
+                "3 resources will be changed, but at most 2 may be",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_violations_none() {
+        let policy: Policy = toml::from_str("[[deny]]\nverbs = [\"replace\"]").unwrap();
+        let diff = diff(&[("aws_db_instance.main", "update")]);
+        assert!(policy.violations(&diff).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        assert!(toml::from_str::<Policy>("max_change = 2").is_err());
+        assert!(toml::from_str::<Policy>("[[deny]]\nverbs = [\"obliterate\"]").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use ratatui::style::{Color, Style};
+
+use crate::model::trowel_diff::TrowelDiff;
+
+const STYLESHEET: &str = "
+body { font-family: sans-serif; margin: 2em; color: #212121; }
+code, td { font-family: monospace; }
+#filters label { margin-right: 1em; font-weight: bold; }
+details.resource { margin: 0.5em 0; border: 1px solid #e0e0e0; border-radius: 4px; padding: 0.5em; }
+details.resource summary { cursor: pointer; }
+.verb { font-weight: bold; }
+table { border-collapse: collapse; margin-top: 0.5em; }
+th, td { border: 1px solid #e0e0e0; padding: 0.25em 0.5em; text-align: left; vertical-align: top; }
+tr.unchanged { color: #9e9e9e; }
+";
+
+/// Hides the resources whose verb has been unticked
+const SCRIPT: &str = "
+document.querySelectorAll('#filters input').forEach(function (input) {
+  input.addEventListener('change', function () {
+    document.querySelectorAll('details.resource[data-verb=\"' + input.value + '\"]')
+      .forEach(function (resource) { resource.hidden = !input.checked; });
+  });
+});
+";
+
+/// Renders a plan as a single HTML page that needs nothing else to be viewed, for reviewers
+/// who don't use a terminal. Resources can be expanded one at a time and filtered by verb.
+pub fn to_html(diff: &TrowelDiff) -> String {
+    let uses = diff.verb_uses_sorted();
+
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html lang=\"en\">".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        "<title>Plan report</title>".to_string(),
+        format!("<style>{}</style>", STYLESHEET),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        "<h1>Plan report</h1>".to_string(),
+    ];
+
+    if uses.is_empty() {
+        lines.push("<p>No changes.</p>".to_string());
+    } else {
+        lines.push("<fieldset id=\"filters\">".to_string());
+        lines.push("<legend>Show</legend>".to_string());
+        for (verb, count) in &uses {
+            lines.push(format!(
+                "<label style=\"color: {}\"><input type=\"checkbox\" value=\"{}\" checked> {} {}</label>",
+                css_color(verb.to_color()),
+                verb.name_lower(),
+                verb.name_lower(),
+                count
+            ));
+        }
+        lines.push("</fieldset>".to_string());
+    }
+
+    for e in diff.entries() {
+        lines.push(format!(
+            "<details class=\"resource\" data-verb=\"{}\">",
+            e.verb.name_lower()
+        ));
+        lines.push(format!(
+            "<summary><span class=\"verb\" style=\"color: {}\">{}</span> <code>{}</code></summary>",
+            css_color(e.verb.to_color()),
+            e.verb.name_lower(),
+            escape_html(&e.resource_path)
+        ));
+        lines.push("<table>".to_string());
+        lines.push("<tr><th>Attribute</th><th>Before</th><th>After</th></tr>".to_string());
+        for (k, v) in e.values_sorted() {
+            lines.push(format!(
+                "<tr class=\"{}\"><td>{}</td>{}{}</tr>",
+                if v.changed() { "changed" } else { "unchanged" },
+                escape_html(k),
+                value_cell(&v.before_plaintext(), v.before_style()),
+                value_cell(&v.after_plaintext(), v.after_style())
+            ));
+        }
+        lines.push("</table>".to_string());
+        lines.push("</details>".to_string());
+    }
+
+    lines.push(format!("<script>{}</script>", SCRIPT));
+    lines.push("</body>".to_string());
+    lines.push("</html>".to_string());
+    lines.join("\n") + "\n"
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A table cell coloured the way the tree view colours the value
+fn value_cell(text: &str, style: Style) -> String {
+    match style.fg {
+        Some(color) => format!(
+            "<td style=\"color: {}; font-weight: bold\">{}</td>",
+            css_color(color),
+            escape_html(text)
+        ),
+        None => format!("<td>{}</td>", escape_html(text)),
+    }
+}
+
+/// Terminal colours, darkened where needed to be readable on a white page
+fn css_color(color: Color) -> &'static str {
+    match color {
+        Color::Green => "#2e7d32",
+        Color::Yellow => "#b58900",
+        Color::Magenta => "#a626a4",
+        Color::Red => "#c62828",
+        Color::Cyan => "#00838f",
+        Color::Blue => "#1565c0",
+        Color::DarkGray => "#9e9e9e",
+        _ => "#616161",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::model::tf_plan::TfPlan;
+
+    use super::*;
+
+    fn diff(action: &str) -> TrowelDiff {
+        let mut change = TfPlan::test_change("random_pet.apple[\"<a>\"]", action);
+        change["name"] = json!("apple");
+        change["index"] = json!("<a>");
+        change["change"]["before"] = json!({"length": 2, "keepers": "old", "separator": "-"});
+        change["change"]["after"] = json!({"length": 3, "keepers": "new", "separator": "-"});
+        change["change"]["before_sensitive"] = json!({"keepers": true});
+        change["change"]["after_sensitive"] = json!({"keepers": true});
+        TrowelDiff::test(vec![change])
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = to_html(&diff("update"));
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<input type=\"checkbox\" value=\"update\" checked> update 1"));
+        assert!(html.contains("<details class=\"resource\" data-verb=\"update\">"));
+        assert!(html.contains("<code>random_pet.apple[&quot;&lt;a&gt;&quot;]</code>"));
+        assert!(html.contains("<tr class=\"changed\"><td>length</td><td>2</td><td>3</td></tr>"));
+        assert!(html.contains("<tr class=\"unchanged\"><td>separator</td>"));
+        // Sensitive values are masked, and coloured as in the tree view
+        assert!(
+            html.contains("<td style=\"color: #a626a4; font-weight: bold\">(sensitive value)</td>")
+        );
+        assert!(!html.contains("&quot;old&quot;") && !html.contains("&quot;new&quot;"));
+    }
+
+    #[test]
+    fn test_to_html_no_changes() {
+        let html = to_html(&diff("no-op"));
+        assert!(html.contains("<p>No changes.</p>"));
+        assert!(!html.contains("<details"));
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::model::{
+    trowel_diff::{TrowelDiff, TrowelDiffEntry, TrowelDiffEntryBefore},
+    verb::Verb,
+};
+
+/// The version of the export schema. The minor version is bumped when fields are added, and
+/// the major version when fields are removed or change meaning.
+pub const FORMAT_VERSION: &str = "1.0";
+
+// The export has its own types so that the schema only changes when it's meant to, rather than
+// whenever trowel's model does
+
+#[derive(Serialize)]
+struct Export<'a> {
+    format_version: &'static str,
+    entries: Vec<ExportEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportEntry<'a> {
+    verb: &'static str,
+    address: &'a str,
+    #[serde(rename = "type")]
+    resource_type: &'a str,
+    provider: &'a str,
+    module: Option<&'a str>,
+    action_reason: Option<&'a str>,
+    replace_paths: &'a [Vec<Value>],
+    /// Sorted, so that output is stable between runs
+    attributes: BTreeMap<&'a str, ExportAttribute<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportAttribute<'a> {
+    before: ExportValue<'a>,
+    after: ExportValue<'a>,
+}
+
+/// A value tagged with its state. Sensitive values are never written out.
+#[derive(Serialize)]
+struct ExportValue<'a> {
+    state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a Value>,
+}
+
+impl<'a> ExportEntry<'a> {
+    fn new(entry: &'a TrowelDiffEntry) -> Self {
+        Self {
+            verb: Self::verb(&entry.verb),
+            address: &entry.resource_path,
+            resource_type: &entry.resource_type,
+            provider: &entry.provider,
+            module: entry.module.as_deref(),
+            action_reason: entry.action_reason.as_deref(),
+            replace_paths: &entry.replace_paths,
+            attributes: entry
+                .values
+                .iter()
+                .map(|(name, value)| {
+                    let attribute = ExportAttribute {
+                        before: ExportValue::new(value.before()),
+                        after: ExportValue::new(value.after()),
+                    };
+                    (name.as_str(), attribute)
+                })
+                .collect(),
+        }
+    }
+
+    fn verb(verb: &Verb) -> &'static str {
+        match verb {
+            Verb::Create => "create",
+            Verb::Update => "update",
+            Verb::Replace => "replace",
+            Verb::Destroy => "destroy",
+            Verb::Read => "read",
+            Verb::Forget => "forget",
+            Verb::Ignore => "ignore",
+        }
+    }
+}
+
+impl<'a> ExportValue<'a> {
+    fn new(value: &'a TrowelDiffEntryBefore) -> Self {
+        let (state, value) = match value {
+            TrowelDiffEntryBefore::Known(value) => ("known", Some(value)),
+            TrowelDiffEntryBefore::Sensitive(_) => ("sensitive", None),
+            TrowelDiffEntryBefore::Unknown => ("unknown", None),
+            TrowelDiffEntryBefore::Absent => ("absent", None),
+        };
+        Self { state, value }
+    }
+}
+
+/// Serialises a plan as trowel sees it, so that other tools don't need to understand the
+/// raw plan format. Sensitive values are never included.
+pub fn to_json(diff: &TrowelDiff) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&Export {
+        format_version: FORMAT_VERSION,
+        entries: diff.entries().iter().map(ExportEntry::new).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::model::tf_plan::TfPlan;
+
+    use super::*;
+
+    fn export() -> String {
+        let mut change = TfPlan::test_change("module.fruit.random_pet.apple", "delete");
+        change["change"]["actions"] = json!(["delete", "create"]);
+        change["change"]["before"] = json!({"length": 2, "keepers": "old", "id": "fond-koala"});
+        change["change"]["after"] = json!({"length": 3, "keepers": "new"});
+        change["change"]["after_unknown"] = json!({"id": true});
+        change["change"]["before_sensitive"] = json!({"keepers": true});
+        change["change"]["after_sensitive"] = json!({"keepers": true});
+        change["change"]["replace_paths"] = json!([["length"], ["tags", 0]]);
+        change["action_reason"] = json!("replace_because_cannot_update");
+        to_json(&TrowelDiff::test(vec![change])).unwrap()
+    }
+
+    #[test]
+    fn test_to_json() {
+        let export: Value = serde_json::from_str(&export()).unwrap();
+        assert_eq!(
+            export,
+            json!({
+                "format_version": FORMAT_VERSION,
+                "entries": [
+                    {
+                        "verb": "replace",
+                        "address": "module.fruit.random_pet.apple",
+                        "type": "random_pet",
+                        "provider": "registry.opentofu.org/hashicorp/random",
+                        "module": "module.fruit",
+                        "action_reason": "replace_because_cannot_update",
+                        "replace_paths": [["length"], ["tags", 0]],
+                        "attributes": {
+                            "id": {
+                                "before": {"state": "known", "value": "fond-koala"},
+                                "after": {"state": "unknown"}
+                            },
+                            "keepers": {
+                                "before": {"state": "sensitive"},
+                                "after": {"state": "sensitive"}
+                            },
+                            "length": {
+                                "before": {"state": "known", "value": 2},
+                                "after": {"state": "known", "value": 3}
+                            }
+                        }
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_json_is_stable() {
+        // Attributes are written in order, however the plan's hash maps were built
+        let export = export();
+        let position = |key: &str| export.find(&format!("\"{}\": {{", key)).unwrap();
+        assert!(position("id") < position("keepers") && position("keepers") < position("length"));
+        // Sensitive values are never written out
+        assert!(!export.contains("old") && !export.contains("new"));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::model::trowel_diff::TrowelDiff;
+
+use super::html::escape_html;
+
+/// Renders a plan as markdown for pull request comments. Everything is folded into
+/// collapsible sections so that long plans don't bury the rest of the conversation.
+pub fn to_markdown(diff: &TrowelDiff) -> String {
+    let entries = diff.entries();
+    let mut lines = vec![
+        "<details>".to_string(),
+        format!("<summary>{}</summary>", verb_counts(diff)),
+        String::new(),
+    ];
+
+    if entries.is_empty() {
+        lines.push("No changes.".to_string());
+        lines.push(String::new());
+    } else {
+        lines.push("| Action | Resource |".to_string());
+        lines.push("| --- | --- |".to_string());
+        for e in entries {
+            lines.push(format!(
+                "| {} | {} |",
+                e.verb.name_lower(),
+                code(&e.resource_path)
+            ));
+        }
+        lines.push(String::new());
+    }
+
+    for e in entries {
+        let changed: Vec<_> = e
+            .values_sorted()
+            .into_iter()
+            .filter(|(_, v)| v.changed())
+            .collect();
+        let unchanged = e.values.len() - changed.len();
+
+        lines.push("<details>".to_string());
+        lines.push(format!(
+            "<summary><code>{}</code> will be {}</summary>",
+            escape_html(&e.resource_path),
+            e.verb.to_past_tense()
+        ));
+        lines.push(String::new());
+        if !changed.is_empty() {
+            lines.push("| Attribute | Before | After |".to_string());
+            lines.push("| --- | --- | --- |".to_string());
+            for (k, v) in changed {
+                lines.push(format!(
+                    "| {} | {} | {} |",
+                    code(k),
+                    code(&v.before_plaintext()),
+                    code(&v.after_plaintext())
+                ));
+            }
+            lines.push(String::new());
+        }
+        if unchanged > 0 {
+            lines.push(format!("_{} unchanged attributes hidden_", unchanged));
+            lines.push(String::new());
+        }
+        lines.push("</details>".to_string());
+        lines.push(String::new());
+    }
+
+    lines.push("</details>".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// A line like `Plan: create 2, destroy 1`, in the same order as the tree view's summary
+fn verb_counts(diff: &TrowelDiff) -> String {
+    let uses = diff.verb_uses_sorted();
+    if uses.is_empty() {
+        return "<b>Plan:</b> no changes".to_string();
+    }
+    let counts: Vec<_> = uses
+        .iter()
+        .map(|(verb, count)| format!("{} {}", verb.name_lower(), count))
+        .collect();
+    format!("<b>Plan:</b> {}", counts.join(", "))
+}
+
+/// Formats text as inline code that is safe to put in a table cell
+fn code(text: &str) -> String {
+    let text = text.replace('|', "\\|");
+    // A code span can only contain backticks if it is delimited by more of them
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::model::tf_plan::TfPlan;
+
+    use super::*;
+
+    fn diff(action: &str) -> TrowelDiff {
+        let mut change = TfPlan::test_change("random_pet.apple[\"a\"]", action);
+        change["name"] = json!("apple");
+        change["index"] = json!("a");
+        change["change"]["before"] =
+            json!({"length": 2, "prefix": "a|b", "keepers": "old", "separator": "-"});
+        change["change"]["after"] =
+            json!({"length": 3, "prefix": "a|b", "keepers": "new", "separator": "-"});
+        change["change"]["before_sensitive"] = json!({"keepers": true});
+        change["change"]["after_sensitive"] = json!({"keepers": true});
+        TrowelDiff::test(vec![change])
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = to_markdown(&diff("update"));
+        assert!(markdown.starts_with("<details>\n<summary><b>Plan:</b> update 1</summary>"));
+        assert!(markdown.contains("| update | `random_pet.apple[\"a\"]` |"));
+        assert!(markdown.contains(
+            "<summary><code>random_pet.apple[&quot;a&quot;]</code> will be updated</summary>"
+        ));
+        assert!(markdown.contains("| `length` | `2` | `3` |"));
+        assert!(markdown.contains("_2 unchanged attributes hidden_"));
+        // Sensitive values are masked
+        assert!(markdown.contains("| `keepers` | `(sensitive value)` | `(sensitive value)` |"));
+        assert!(!markdown.contains("old") && !markdown.contains("new"));
+        assert!(markdown.ends_with("</details>\n"));
+    }
+
+    #[test]
+    fn test_to_markdown_no_changes() {
+        let markdown = to_markdown(&diff("no-op"));
+        assert!(markdown.contains("<b>Plan:</b> no changes"));
+        assert!(markdown.contains("No changes."));
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(code("\"a|b\""), "`\"a\\|b\"`");
+        assert_eq!(code("a`b"), "`` a`b ``");
+    }
+}
@@ -1,17 +1,34 @@
-use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::event::{Event, KeyEvent, KeyEventKind};
 
-use crate::model::trowel_diff::TrowelDiff;
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::{tf_version::TfVersion, trowel_diff::TrowelDiff},
+};
 
-use super::{text_view_state::TextViewState, tree_view_state::TreeViewState};
+use super::{
+    help_view_state::HelpViewState,
+    text_view_state::TextViewState,
+    tree_view_state::TreeViewState,
+    workspace_view_state::{PlannedWorkspace, WorkspaceViewState},
+};
 
 pub enum Lifecycle {
     Running,
     Quit,
 }
 
+#[allow(clippy::enum_variant_names)] // Variants are named after the widgets they show
 pub enum ActiveView {
     TreeView,
     TextView,
+    WorkspaceView,
+}
+
+/// Progress of re-planning when configuration changes, in watch mode
+pub enum WatchStatus {
+    Watching,
+    Replanning,
+    Failed(String),
 }
 
 pub struct AppState {
@@ -20,6 +37,12 @@ pub struct AppState {
     pub text_view_state: Option<TextViewState>,
     pub tree_view_state: TreeViewState,
     pub show_experimental_warning: bool,
+    pub tf_version: Option<TfVersion>,
+    pub workspace_view_state: Option<WorkspaceViewState>,
+    pub watch_status: Option<WatchStatus>,
+    pub keymap: Keymap,
+    /// The keys for the active view, when they're shown over it
+    pub help: HelpViewState,
 }
 
 impl AppState {
@@ -27,6 +50,10 @@ impl AppState {
         diff: TrowelDiff,
         text_plan: Option<String>,
         show_experimental_warning: bool,
+        tf_version: Option<TfVersion>,
+        workspace_view_state: Option<WorkspaceViewState>,
+        watching: bool,
+        keymap: Keymap,
     ) -> AppState {
         AppState {
             lifecycle: Lifecycle::Running,
@@ -34,49 +61,197 @@ impl AppState {
             text_view_state: text_plan.map(TextViewState::new),
             tree_view_state: TreeViewState::new(diff),
             show_experimental_warning,
+            tf_version,
+            workspace_view_state,
+            watch_status: watching.then_some(WatchStatus::Watching),
+            keymap,
+            help: HelpViewState::default(),
         }
     }
 
     pub fn process_event(&mut self, event: Event) {
         match event {
             Event::Key(key) if !matches!(key.kind, KeyEventKind::Press) => (),
-            Event::Key(key) if Self::is_quit_binding(&key) => self.quit(),
-            Event::Key(key) if key.code == KeyCode::Tab => self.toggle_view(),
-            Event::Key(key) => match self.active_view {
-                ActiveView::TreeView => {
-                    self.tree_view_state.process_keypress(&key);
-                }
+            // Keys typed into a search or filter are text rather than commands, apart from
+            // quitting with keys that can't be typed
+            Event::Key(key) if self.is_editing() && self.keymap.is_quit_while_typing(&key) => {
+                self.quit()
+            }
+            Event::Key(key) if self.is_editing() => match self.active_view {
+                ActiveView::TextView => self.process_text_keypress(&key),
+                _ => self.tree_view_state.process_keypress(&key, &self.keymap),
+            },
+            Event::Key(key) => self.process_keypress(&key),
+            Event::Mouse(mouse) => match self.active_view {
+                ActiveView::TreeView => self.tree_view_state.process_mouse_event(mouse),
                 ActiveView::TextView => {
                     if let Some(state) = self.text_view_state.as_mut() {
-                        state.process_keypress(&key);
+                        state.process_mouse_event(mouse);
                     }
+                    self.select_resource_at_top();
                 }
-            },
-            Event::Mouse(mouse) => match self.active_view {
-                ActiveView::TreeView => self.tree_view_state.process_mouse_event(mouse),
-                ActiveView::TextView => (),
+                ActiveView::WorkspaceView => (),
             },
             Event::Resize(_, _) => (),
             _ => (),
         }
     }
 
+    pub fn finish_workspace_plan(&mut self, name: &str, result: Result<PlannedWorkspace, String>) {
+        let replacement = self
+            .workspace_view_state
+            .as_mut()
+            .and_then(|state| state.finish_plan(name, result));
+        if let Some(planned) = replacement {
+            self.display(planned);
+        }
+    }
+
+    /// The workspace to re-plan when configuration changes, if it isn't the selected one
+    pub fn start_replan(&mut self) -> Option<String> {
+        self.watch_status = Some(WatchStatus::Replanning);
+        self.workspace_view_state
+            .as_ref()
+            .map(|state| state.displayed.clone())
+    }
+
+    pub fn finish_replan(
+        &mut self,
+        workspace: Option<String>,
+        result: Result<PlannedWorkspace, String>,
+    ) {
+        let planned = match result {
+            Ok(planned) => planned,
+            Err(message) => {
+                self.watch_status = Some(WatchStatus::Failed(message));
+                return;
+            }
+        };
+        self.watch_status = Some(WatchStatus::Watching);
+        // The displayed workspace may have changed while planning, in which case the new plan
+        // is only kept for when that workspace is displayed again
+        let replacement = match (workspace, self.workspace_view_state.as_mut()) {
+            (Some(name), Some(state)) => state.finish_plan(&name, Ok(planned)),
+            _ => Some(planned),
+        };
+        if let Some(planned) = replacement {
+            self.refresh(planned);
+        }
+    }
+
+    /// Replaces the displayed plan with a newer one for the same workspace, keeping the view where it was
+    fn refresh(&mut self, planned: PlannedWorkspace) {
+        self.tree_view_state.replace_diff(planned.diff);
+        let offset = self.text_view_state.as_ref().map(TextViewState::offset);
+        self.text_view_state = planned.text_plan.map(TextViewState::new);
+        if let (Some(state), Some(offset)) = (self.text_view_state.as_mut(), offset) {
+            state.set_offset(offset);
+        }
+    }
+
+    /// Replaces the displayed plan, e.g. with that of another workspace
+    pub fn display(&mut self, planned: PlannedWorkspace) {
+        self.tree_view_state = TreeViewState::new(planned.diff);
+        self.text_view_state = planned.text_plan.map(TextViewState::new);
+    }
+
+    fn process_keypress(&mut self, key: &KeyEvent) {
+        // Only the help and quitting use keys while the help is shown
+        if self.help.process_keypress(key, &self.keymap) {
+            return;
+        }
+        match self.keymap.action(KeyContext::Anywhere, key) {
+            Some(Action::Quit) => self.quit(),
+            Some(Action::ToggleView) => self.toggle_view(),
+            Some(Action::ToggleWorkspaces) => self.toggle_workspace_view(),
+            _ => match self.active_view {
+                ActiveView::TreeView => {
+                    self.tree_view_state.process_keypress(key, &self.keymap);
+                }
+                ActiveView::TextView => self.process_text_keypress(key),
+                ActiveView::WorkspaceView => {
+                    let chosen = self
+                        .workspace_view_state
+                        .as_mut()
+                        .and_then(|state| state.process_keypress(key, &self.keymap));
+                    if let Some(planned) = chosen {
+                        self.display(planned);
+                        self.active_view = ActiveView::TreeView;
+                    }
+                }
+            },
+        }
+    }
+
+    /// Where keys are being pressed, which decides what they do
+    pub fn key_context(&self) -> KeyContext {
+        match self.active_view {
+            ActiveView::TreeView => KeyContext::Tree,
+            ActiveView::TextView => KeyContext::Text,
+            ActiveView::WorkspaceView => KeyContext::Workspaces,
+        }
+    }
+
+    fn is_editing(&self) -> bool {
+        match self.active_view {
+            ActiveView::TreeView => self.tree_view_state.is_editing(),
+            ActiveView::TextView => self
+                .text_view_state
+                .as_ref()
+                .is_some_and(|state| state.is_editing()),
+            ActiveView::WorkspaceView => false,
+        }
+    }
+
+    fn process_text_keypress(&mut self, key: &KeyEvent) {
+        if let Some(state) = self.text_view_state.as_mut() {
+            state.process_keypress(key, &self.keymap);
+        }
+        self.select_resource_at_top();
+    }
+
     fn toggle_view(&mut self) {
         self.active_view = match self.active_view {
             ActiveView::TreeView => ActiveView::TextView,
             ActiveView::TextView => ActiveView::TreeView,
+            ActiveView::WorkspaceView => ActiveView::TreeView,
+        };
+        if let ActiveView::TextView = self.active_view {
+            self.scroll_to_selected_resource();
         }
     }
 
-    fn quit(&mut self) {
-        self.lifecycle = Lifecycle::Quit;
+    /// Scrolls the text view to the block of the resource selected in the tree
+    fn scroll_to_selected_resource(&mut self) {
+        let Some(address) = self.tree_view_state.tree_state.selected().first() else {
+            return;
+        };
+        if let Some(state) = self.text_view_state.as_mut()
+            && let Some(line) = state.resource_line(address)
+        {
+            state.scroll_to_line(line);
+        }
+    }
+
+    /// Selects the resource whose block is at the top of the text view in the tree
+    fn select_resource_at_top(&mut self) {
+        let Some(state) = self.text_view_state.as_ref() else {
+            return;
+        };
+        if let Some(address) = state.resource_at_top().map(String::from) {
+            self.tree_view_state.select_resource(&address);
+        }
     }
 
-    fn is_quit_binding(key: &KeyEvent) -> bool {
-        match key.code {
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => true,
-            KeyCode::Char('q') => true,
-            _ => false,
+    fn toggle_workspace_view(&mut self) {
+        self.active_view = match (&self.active_view, &self.workspace_view_state) {
+            (ActiveView::WorkspaceView, _) => ActiveView::TreeView,
+            (_, Some(_)) => ActiveView::WorkspaceView,
+            (_, None) => return,
         }
     }
+
+    fn quit(&mut self) {
+        self.lifecycle = Lifecycle::Quit;
+    }
 }
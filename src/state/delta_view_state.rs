@@ -0,0 +1,46 @@
+use ratatui::crossterm::event::{Event, KeyEventKind};
+use tui_tree_widget::TreeState;
+
+use crate::{
+    keymap::{KeyContext, Keymap},
+    model::trowel_delta::TrowelDelta,
+};
+
+use super::{app_state::Lifecycle, help_view_state::HelpViewState, tree_view_state::TreeViewState};
+
+pub struct DeltaViewState {
+    pub lifecycle: Lifecycle,
+    pub delta: TrowelDelta,
+    pub tree_state: TreeState<String>,
+    pub keymap: Keymap,
+    pub help: HelpViewState,
+}
+
+impl DeltaViewState {
+    pub fn new(delta: TrowelDelta, keymap: Keymap) -> Self {
+        Self {
+            lifecycle: Lifecycle::Running,
+            delta,
+            tree_state: TreeState::default(),
+            keymap,
+            help: HelpViewState::default(),
+        }
+    }
+
+    pub fn process_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if !matches!(key.kind, KeyEventKind::Press) => (),
+            Event::Key(key) if self.help.process_keypress(&key, &self.keymap) => (),
+            Event::Key(key) if self.keymap.is_quit(&key) => self.lifecycle = Lifecycle::Quit,
+            Event::Key(key) => {
+                if let Some(action) = self.keymap.action(KeyContext::Tree, &key) {
+                    TreeViewState::navigate(&mut self.tree_state, action);
+                }
+            }
+            Event::Mouse(mouse) => {
+                TreeViewState::navigate_with_mouse(&mut self.tree_state, mouse);
+            }
+            _ => (),
+        }
+    }
+}
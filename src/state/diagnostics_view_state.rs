@@ -0,0 +1,96 @@
+use ratatui::{
+    crossterm::event::{Event, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind},
+    widgets::ListState,
+};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::tf_event::TfEventDiagnostic,
+};
+
+use super::{app_state::Lifecycle, help_view_state::HelpViewState};
+
+pub struct DiagnosticsViewState {
+    pub lifecycle: Lifecycle,
+    pub diagnostics: Vec<TfEventDiagnostic>,
+    pub list_state: ListState,
+    /// How many rows of the selected diagnostic's detail are scrolled past
+    pub detail_scroll: usize,
+    pub help: HelpViewState,
+}
+
+impl DiagnosticsViewState {
+    pub fn new(mut diagnostics: Vec<TfEventDiagnostic>) -> Self {
+        // Errors are what made the plan fail, so they go first
+        diagnostics.sort_by_key(|d| !d.is_error());
+        let selected = if diagnostics.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        Self {
+            lifecycle: Lifecycle::Running,
+            diagnostics,
+            list_state: ListState::default().with_selected(selected),
+            detail_scroll: 0,
+            help: HelpViewState::default(),
+        }
+    }
+
+    pub fn selected(&self) -> Option<&TfEventDiagnostic> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.diagnostics.get(i))
+    }
+
+    pub fn process_event(&mut self, event: Event, keymap: &Keymap) {
+        match event {
+            Event::Key(key) if !matches!(key.kind, KeyEventKind::Press) => (),
+            Event::Key(key) if self.help.process_keypress(&key, keymap) => (),
+            Event::Key(key) if keymap.is_quit(&key) => self.lifecycle = Lifecycle::Quit,
+            Event::Key(key) => self.process_keypress(&key, keymap),
+            Event::Mouse(mouse) => self.process_mouse_event(mouse),
+            _ => (),
+        }
+    }
+
+    fn process_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        let selected = self.list_state.selected();
+        match keymap.action(KeyContext::Diagnostics, key) {
+            // Basic navigation
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::Up) => self.list_state.select_previous(),
+
+            // Jump to top and bottom
+            Some(Action::Top) => self.list_state.select_first(),
+            Some(Action::Bottom) => self.list_state.select_last(),
+
+            // The detail is clamped to its wrapped rows when it's rendered
+            Some(Action::ScrollDetailDown) => {
+                self.detail_scroll = self.detail_scroll.saturating_add(1)
+            }
+            Some(Action::ScrollDetailUp) => {
+                self.detail_scroll = self.detail_scroll.saturating_sub(1)
+            }
+            _ => (),
+        }
+        self.show_selected_from_top(selected);
+    }
+
+    fn process_mouse_event(&mut self, mouse: MouseEvent) {
+        let selected = self.list_state.selected();
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.list_state.select_next(),
+            MouseEventKind::ScrollUp => self.list_state.select_previous(),
+            _ => (),
+        }
+        self.show_selected_from_top(selected);
+    }
+
+    /// Each diagnostic's detail is shown from the top
+    fn show_selected_from_top(&mut self, previous: Option<usize>) {
+        if self.list_state.selected() != previous {
+            self.detail_scroll = 0;
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use ratatui::crossterm::event::KeyEvent;
+
+use crate::keymap::{Action, KeyContext, Keymap};
+
+/// Whether the keys of a view are shown over it, and how far down they're scrolled
+#[derive(Default)]
+pub struct HelpViewState {
+    pub shown: bool,
+    /// How many bindings are scrolled past, which is clamped when the help is rendered
+    pub scroll: usize,
+    /// How many bindings fitted in the help when it was last rendered
+    pub page_rows: usize,
+}
+
+impl HelpViewState {
+    /// Handles a key if it opens the help, or if the help is shown. Quitting is left to the
+    /// view, and returns false like any other key the help doesn't use.
+    pub fn process_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) -> bool {
+        let action = keymap.action(KeyContext::Anywhere, key);
+        if !self.shown {
+            if action == Some(Action::ToggleHelp) {
+                self.shown = true;
+                self.scroll = 0;
+            }
+            return self.shown;
+        }
+        match action.or_else(|| keymap.action(KeyContext::Help, key)) {
+            Some(Action::Quit) => return false,
+            Some(Action::ToggleHelp) => self.shown = false,
+
+            // Basic navigation
+            Some(Action::Down) => self.scroll = self.scroll.saturating_add(1),
+            Some(Action::Up) => self.scroll = self.scroll.saturating_sub(1),
+            Some(Action::PageDown) => self.scroll = self.scroll.saturating_add(self.page_rows),
+            Some(Action::PageUp) => self.scroll = self.scroll.saturating_sub(self.page_rows),
+
+            // Jump to top and bottom
+            Some(Action::Top) => self.scroll = 0,
+            Some(Action::Bottom) => self.scroll = usize::MAX,
+            _ => (),
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyCode;
+
+    use super::*;
+
+    #[test]
+    fn test_process_keypress() {
+        let keymap = Keymap::default();
+        let press = |state: &mut HelpViewState, code| {
+            state.process_keypress(&KeyEvent::from(code), &keymap)
+        };
+        let mut state = HelpViewState::default();
+        assert!(!press(&mut state, KeyCode::Char('j')));
+        assert!(press(&mut state, KeyCode::Char('?')));
+        assert!(state.shown);
+
+        // Keys scroll the help rather than reaching the view beneath it
+        assert!(press(&mut state, KeyCode::Char('j')));
+        assert!(press(&mut state, KeyCode::Char('j')));
+        assert_eq!(state.scroll, 2);
+        assert!(press(&mut state, KeyCode::Char('k')));
+        assert_eq!(state.scroll, 1);
+
+        // Quitting is left to the view
+        assert!(!press(&mut state, KeyCode::Char('q')));
+        assert!(press(&mut state, KeyCode::Esc));
+        assert!(!state.shown);
+    }
+}
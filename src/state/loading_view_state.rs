@@ -0,0 +1,19 @@
+pub struct LoadingViewState {
+    pub diff_loaded: bool,
+    pub text_plan_loaded: bool,
+    pub ticks: usize,
+}
+
+impl LoadingViewState {
+    pub fn new() -> Self {
+        Self {
+            diff_loaded: false,
+            text_plan_loaded: false,
+            ticks: 0,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+    }
+}
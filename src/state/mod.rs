@@ -1,4 +1,10 @@
 pub mod app_state;
+pub mod delta_view_state;
+pub mod diagnostics_view_state;
+pub mod help_view_state;
+pub mod loading_view_state;
 pub mod planning_view_state;
+pub mod state_view_state;
 pub mod text_view_state;
 pub mod tree_view_state;
+pub mod workspace_view_state;
@@ -1,31 +1,136 @@
+use ratatui::crossterm::event::{Event, KeyEventKind};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
+use crate::{
+    keymap::Keymap,
+    model::{
+        tf_event::{TfEvent, TfEventBody, TfEventChange, TfEventChangeSummary, TfEventDiagnostic},
+        tf_version::TfVersion,
+    },
+};
+
+use super::help_view_state::HelpViewState;
+
+pub enum PlanningStatus {
+    Running,
+    Interrupting,
+    Interrupted,
+    /// The plan failed because the working directory needs to be initialized first
+    InitRequired,
+    Initializing,
+}
+
 pub struct PlanningViewState {
-    rx: Receiver<String>,
-    pub plan_stdout: Vec<String>,
+    rx: Receiver<TfEvent>,
+    interrupt_tx: Sender<()>,
+    pub status: PlanningStatus,
+    pub tf_version: Option<TfVersion>,
+    pub last_message: Option<String>,
+    pub refreshed_count: usize,
+    pub refreshing: Vec<String>,
+    pub ephemeral_opened_count: usize,
+    pub planned_changes: Vec<TfEventChange>,
+    pub change_summary: Option<TfEventChangeSummary>,
+    pub diagnostics: Vec<TfEventDiagnostic>,
+    pub init_output: Vec<String>,
+    pub help: HelpViewState,
 }
 
 impl PlanningViewState {
     const MPSC_BUFFER_SIZE: usize = 100;
 
-    pub fn new() -> (Self, Sender<String>) {
+    pub fn new(tf_version: Option<TfVersion>) -> (Self, Sender<TfEvent>, Receiver<()>) {
         let (tx, rx) = mpsc::channel(Self::MPSC_BUFFER_SIZE);
+        let (interrupt_tx, interrupt_rx) = mpsc::channel(Self::MPSC_BUFFER_SIZE);
         (
             Self {
                 rx,
-                plan_stdout: Vec::new(),
+                interrupt_tx,
+                status: PlanningStatus::Running,
+                tf_version,
+                last_message: None,
+                refreshed_count: 0,
+                refreshing: Vec::new(),
+                ephemeral_opened_count: 0,
+                planned_changes: Vec::new(),
+                change_summary: None,
+                diagnostics: Vec::new(),
+                init_output: Vec::new(),
+                help: HelpViewState::default(),
             },
             tx,
+            interrupt_rx,
         )
     }
 
-    pub async fn next_line(&mut self) -> Option<()> {
+    /// Starts over for another process, e.g. running init and then planning again. Returns new
+    /// channels since the previous process hung up when it finished.
+    pub fn restart(&mut self, status: PlanningStatus) -> (Sender<TfEvent>, Receiver<()>) {
+        let (state, tx, interrupt_rx) = Self::new(self.tf_version.clone());
+        *self = Self {
+            status,
+            help: std::mem::take(&mut self.help),
+            ..state
+        };
+        (tx, interrupt_rx)
+    }
+
+    pub async fn next_event(&mut self) -> Option<()> {
         tokio::select! {
-            Some(line) = self.rx.recv() => {
-                self.plan_stdout.push(line);
+            Some(event) = self.rx.recv() => {
+                self.process_tf_event(event);
                 Some(())
             }
             else => None
         }
     }
+
+    /// Whether the plan process has hung up and every event it sent has been processed
+    pub fn is_drained(&self) -> bool {
+        self.rx.is_closed() && self.rx.is_empty()
+    }
+
+    pub fn process_event(&mut self, event: Event, keymap: &Keymap) {
+        match event {
+            Event::Key(key) if !matches!(key.kind, KeyEventKind::Press) => (),
+            Event::Key(key) if self.help.process_keypress(&key, keymap) => (),
+            Event::Key(key) if keymap.is_quit(&key) => self.interrupt(),
+            _ => (),
+        }
+    }
+
+    /// Asks the plan process to shut down. Repeating this forwards another interrupt, which
+    /// OpenTofu treats as a request to stop without waiting for a graceful shutdown.
+    fn interrupt(&mut self) {
+        if matches!(self.status, PlanningStatus::Interrupted) {
+            return;
+        }
+        self.interrupt_tx.try_send(()).ok();
+        self.status = PlanningStatus::Interrupting;
+    }
+
+    /// The resource most recently started refreshing that has yet to complete
+    pub fn currently_refreshing(&self) -> Option<&String> {
+        self.refreshing.last()
+    }
+
+    fn process_tf_event(&mut self, event: TfEvent) {
+        if matches!(self.status, PlanningStatus::Initializing) && !event.message.is_empty() {
+            self.init_output.push(event.message.clone());
+        }
+        self.last_message = Some(event.message);
+        match event.body {
+            TfEventBody::RefreshStart { hook } => self.refreshing.push(hook.resource.addr),
+            TfEventBody::RefreshComplete { hook } => {
+                self.refreshing.retain(|addr| *addr != hook.resource.addr);
+                self.refreshed_count += 1;
+            }
+            TfEventBody::EphemeralOpStart { .. } => (),
+            TfEventBody::EphemeralOpComplete { .. } => self.ephemeral_opened_count += 1,
+            TfEventBody::PlannedChange { change } => self.planned_changes.push(change),
+            TfEventBody::ChangeSummary { changes } => self.change_summary = Some(changes),
+            TfEventBody::Diagnostic { diagnostic } => self.diagnostics.push(diagnostic),
+            TfEventBody::Other => (),
+        }
+    }
 }
@@ -0,0 +1,94 @@
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use tui_tree_widget::TreeState;
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::trowel_state::TrowelState,
+};
+
+use super::{app_state::Lifecycle, help_view_state::HelpViewState, tree_view_state::TreeViewState};
+
+pub struct StateViewState {
+    pub lifecycle: Lifecycle,
+    pub state: TrowelState,
+    pub tree_state: TreeState<String>,
+    /// Only entries matching the query are shown
+    pub query: String,
+    /// Whether keypresses are being typed into the query
+    pub editing_query: bool,
+    pub keymap: Keymap,
+    pub help: HelpViewState,
+}
+
+impl StateViewState {
+    pub fn new(state: TrowelState, keymap: Keymap) -> Self {
+        Self {
+            lifecycle: Lifecycle::Running,
+            state,
+            tree_state: TreeState::default(),
+            query: String::new(),
+            editing_query: false,
+            keymap,
+            help: HelpViewState::default(),
+        }
+    }
+
+    pub fn process_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if !matches!(key.kind, KeyEventKind::Press) => (),
+            Event::Key(key) if self.editing_query => self.process_query_keypress(&key),
+            Event::Key(key) if self.help.process_keypress(&key, &self.keymap) => (),
+            Event::Key(key) if self.keymap.is_quit(&key) => self.lifecycle = Lifecycle::Quit,
+            Event::Key(key) => self.process_keypress(&key),
+            Event::Mouse(mouse) => {
+                TreeViewState::navigate_with_mouse(&mut self.tree_state, mouse);
+            }
+            _ => (),
+        }
+    }
+
+    fn process_keypress(&mut self, key: &KeyEvent) {
+        let Some(action) = self.keymap.action(KeyContext::Tree, key) else {
+            return;
+        };
+        match action {
+            // Search
+            Action::Search => self.editing_query = true,
+            Action::ClearSearch => self.set_query(String::new()),
+            _ => {
+                TreeViewState::navigate(&mut self.tree_state, action);
+            }
+        }
+    }
+
+    fn process_query_keypress(&mut self, key: &KeyEvent) {
+        if self.keymap.is_quit_while_typing(key) {
+            self.lifecycle = Lifecycle::Quit;
+            return;
+        }
+        match (self.keymap.action(KeyContext::Typing, key), key.code) {
+            (Some(Action::FinishTyping), _) => self.editing_query = false,
+            (Some(Action::CancelTyping), _) => {
+                self.editing_query = false;
+                self.set_query(String::new());
+            }
+            (_, KeyCode::Char(c)) => {
+                let mut query = self.query.clone();
+                query.push(c);
+                self.set_query(query);
+            }
+            (_, KeyCode::Backspace) => {
+                let mut query = self.query.clone();
+                query.pop();
+                self.set_query(query);
+            }
+            _ => (),
+        }
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        // The selection may have been filtered out, so start again from the top
+        self.tree_state = TreeState::default();
+    }
+}
@@ -1,37 +1,435 @@
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
-use tui_scrollview::ScrollViewState;
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind},
+    layout::Size,
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::text_search::contains_match,
+};
 
 pub struct TextViewState {
     pub plan: String,
-    pub scroll_view_state: ScrollViewState,
+    /// The row of the wrapped plan at the top of the view
+    offset: usize,
+    /// Text searched for in the plan, highlighted wherever it appears
+    pub query: String,
+    /// Whether keys are typed into the query rather than used to navigate
+    pub editing_query: bool,
+    /// The top line when the query started being typed, which searching starts from
+    query_origin: usize,
+    /// The row each line of the plan starts on once wrapped, followed by the number of rows
+    /// in all. Each line takes one row until the plan has been wrapped to a width.
+    line_rows: Vec<usize>,
+    /// The width the plan was wrapped to
+    pub wrap_width: u16,
+    /// The size of the view the plan was last wrapped to fit
+    wrapped_for: Size,
+    /// The line starting each resource's block and the resource's address, in order of line
+    headers: Vec<(usize, String)>,
 }
 
 impl TextViewState {
+    /// How a resource's address is followed in the comment that starts its block in the plan,
+    /// e.g. `# aws_instance.web will be updated in-place`. Drifted resources, which are
+    /// described before the planned changes as e.g. `# aws_instance.web has changed`, aren't
+    /// included.
+    const HEADER_SUFFIXES: [&str; 4] = [" will ", " must ", " is tainted", " (deposed object"];
+
     pub fn new(plan: String) -> Self {
+        let headers = plan
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| Some((i, Self::header_address(line)?.to_string())))
+            .collect();
         Self {
             plan,
-            scroll_view_state: ScrollViewState::new(),
+            offset: 0,
+            query: String::new(),
+            editing_query: false,
+            query_origin: 0,
+            line_rows: Vec::new(),
+            wrap_width: 0,
+            wrapped_for: Size::ZERO,
+            headers,
         }
     }
 
-    pub fn process_keypress(&mut self, key: &KeyEvent) {
-        const PAGE_DOWN_LINES: u16 = 10;
+    pub fn is_editing(&self) -> bool {
+        self.editing_query
+    }
+
+    pub fn process_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        const PAGE_DOWN_LINES: usize = 10;
 
-        match key.code {
+        if self.editing_query {
+            self.process_query_keypress(key, keymap);
+            return;
+        }
+        let Some(action) = keymap.action(KeyContext::Text, key) else {
+            return;
+        };
+        match action {
             // Basic navigation
-            KeyCode::Char('j') => self.scroll_view_state.scroll_down(),
-            KeyCode::Char('k') => self.scroll_view_state.scroll_up(),
-            KeyCode::Char('u') => {
-                for _ in 0..PAGE_DOWN_LINES {
-                    self.scroll_view_state.scroll_up();
-                }
+            Action::Down => self.scroll_down(1),
+            Action::Up => self.scroll_up(1),
+            Action::PageDown => self.scroll_down(self.page_rows()),
+            Action::PageUp => self.scroll_up(self.page_rows()),
+            Action::UpMany => self.scroll_up(PAGE_DOWN_LINES),
+            Action::DownMany => self.scroll_down(PAGE_DOWN_LINES),
+
+            // Jump to top and bottom
+            Action::Top => self.offset = 0,
+            Action::Bottom => self.offset = self.max_offset(),
+
+            // Resource blocks
+            Action::NextResource => self.scroll_to_resource(true),
+            Action::PreviousResource => self.scroll_to_resource(false),
+
+            // Search
+            Action::Search => {
+                self.editing_query = true;
+                self.query_origin = self.top_line();
+                self.query.clear();
+            }
+            Action::NextMatch => self.scroll_to_match(true),
+            Action::PreviousMatch => self.scroll_to_match(false),
+            Action::ClearSearch => self.query.clear(),
+            _ => (),
+        }
+    }
+
+    pub fn process_mouse_event(&mut self, mouse: MouseEvent) {
+        const WHEEL_LINES: usize = 3;
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.scroll_down(WHEEL_LINES),
+            MouseEventKind::ScrollUp => self.scroll_up(WHEEL_LINES),
+            _ => (),
+        }
+    }
+
+    fn scroll_down(&mut self, rows: usize) {
+        self.offset = self.offset.saturating_add(rows).min(self.max_offset());
+    }
+
+    fn scroll_up(&mut self, rows: usize) {
+        self.offset = self.offset.saturating_sub(rows);
+    }
+
+    /// How far a page scrolls, which is the height of the view less a row to keep in sight
+    fn page_rows(&self) -> usize {
+        usize::from(self.wrapped_for.height.saturating_sub(1)).max(1)
+    }
+
+    /// The furthest the plan can be scrolled, so that its end is at the bottom of the view
+    fn max_offset(&self) -> usize {
+        self.height()
+            .saturating_sub(usize::from(self.wrapped_for.height))
+    }
+
+    /// Keeps the end of the plan from being scrolled above the bottom of the view
+    pub fn clamp_offset(&mut self) {
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    fn process_query_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        match (keymap.action(KeyContext::Typing, key), key.code) {
+            (Some(Action::FinishTyping), _) => self.editing_query = false,
+            (Some(Action::CancelTyping), _) => {
+                self.editing_query = false;
+                self.query.clear();
+                self.scroll_to_line(self.query_origin);
+            }
+            (_, KeyCode::Char(c)) => {
+                self.query.push(c);
+                self.scroll_to_first_match();
             }
-            KeyCode::Char('d') => {
-                for _ in 0..PAGE_DOWN_LINES {
-                    self.scroll_view_state.scroll_down();
-                }
+            (_, KeyCode::Backspace) => {
+                self.query.pop();
+                self.scroll_to_first_match();
             }
             _ => (),
         }
     }
+
+    /// The lines containing the query
+    pub fn matching_lines(&self) -> Vec<usize> {
+        self.plan
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| contains_match(line, &self.query))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The position of the match at the top of the view among all matches, and how many there are
+    pub fn match_position(&self) -> (Option<usize>, usize) {
+        let matches = self.matching_lines();
+        let top = self.top_line();
+        (matches.iter().position(|&l| l == top), matches.len())
+    }
+
+    /// Scrolls to the first match from where the search started, as the query is typed
+    fn scroll_to_first_match(&mut self) {
+        let matches = self.matching_lines();
+        let line = matches
+            .iter()
+            .find(|&&l| l >= self.query_origin)
+            .or(matches.first());
+        self.scroll_to_line(line.copied().unwrap_or(self.query_origin));
+    }
+
+    /// Scrolls to the next or previous match, wrapping around at the ends
+    fn scroll_to_match(&mut self, forward: bool) {
+        let matches = self.matching_lines();
+        let wrapped = if forward {
+            matches.first()
+        } else {
+            matches.last()
+        };
+        if let Some(line) =
+            Self::line_after(&matches, self.top_line(), forward).or(wrapped.copied())
+        {
+            self.scroll_to_line(line);
+        }
+    }
+
+    /// Scrolls to the start of the next or previous resource's block
+    fn scroll_to_resource(&mut self, forward: bool) {
+        let headers: Vec<_> = self.headers.iter().map(|(line, _)| *line).collect();
+        if let Some(line) = Self::line_after(&headers, self.top_line(), forward) {
+            self.scroll_to_line(line);
+        }
+    }
+
+    /// The first of the sorted lines after the given one, or the last before it
+    fn line_after(lines: &[usize], from: usize, forward: bool) -> Option<usize> {
+        if forward {
+            lines.iter().find(|&&l| l > from).copied()
+        } else {
+            lines.iter().rev().find(|&&l| l < from).copied()
+        }
+    }
+
+    /// How many rows the plan takes up once wrapped
+    pub fn height(&self) -> usize {
+        match self.line_rows.last() {
+            Some(rows) => *rows,
+            None => self.plan.lines().count(),
+        }
+    }
+
+    /// Wraps the plan to fit a view, leaving a column for the scrollbar if it's too long to fit
+    /// in the view's height. The same line stays at the top of the view.
+    pub fn wrap(&mut self, view: Size) {
+        if view == self.wrapped_for && !self.line_rows.is_empty() {
+            return;
+        }
+        let top = self.top_line();
+        self.wrap_lines(view.width);
+        if self.height() > usize::from(view.height) {
+            self.wrap_lines(view.width.saturating_sub(1));
+        }
+        self.wrapped_for = view;
+        self.scroll_to_line(top);
+    }
+
+    /// Works out how many rows each line of the plan wraps onto at a width
+    fn wrap_lines(&mut self, width: u16) {
+        let mut row = 0;
+        self.line_rows = vec![row];
+        for line in self.plan.lines() {
+            let height = Paragraph::new(Line::from(line))
+                .wrap(Wrap { trim: false })
+                .line_count(width)
+                .max(1);
+            row += height;
+            self.line_rows.push(row);
+        }
+        self.wrap_width = width;
+    }
+
+    /// The line of the plan at the top of the view
+    pub fn top_line(&self) -> usize {
+        if self.line_rows.is_empty() {
+            return self.offset;
+        }
+        // The last line starting at or above the top row
+        self.line_rows
+            .partition_point(|&row| row <= self.offset)
+            .saturating_sub(1)
+    }
+
+    /// How many rows of the top line are scrolled above the view, once it's wrapped
+    pub fn top_line_rows_hidden(&self) -> usize {
+        let start = self.line_rows.get(self.top_line()).copied();
+        self.offset - start.unwrap_or(self.offset)
+    }
+
+    /// Scrolls so that the line of the plan is at the top of the view
+    pub fn scroll_to_line(&mut self, line: usize) {
+        self.offset = self.line_rows.get(line).copied().unwrap_or(line);
+    }
+
+    /// The line starting the block that describes the change to a resource
+    pub fn resource_line(&self, address: &str) -> Option<usize> {
+        self.headers
+            .iter()
+            .find(|(_, a)| a == address)
+            .map(|(line, _)| *line)
+    }
+
+    /// The resource whose block is at the top of the view
+    pub fn resource_at_top(&self) -> Option<&str> {
+        let above = self
+            .headers
+            .partition_point(|(line, _)| *line <= self.top_line());
+        let (_, address) = self.headers.get(above.checked_sub(1)?)?;
+        Some(address)
+    }
+
+    /// The address of the resource whose block the line starts, if it starts one
+    fn header_address(line: &str) -> Option<&str> {
+        let comment = line.trim_start().strip_prefix("# ")?;
+        let end = Self::HEADER_SUFFIXES
+            .iter()
+            .filter_map(|suffix| comment.find(suffix))
+            .min()?;
+        comment.get(..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = "\
+Note: Objects have changed outside of OpenTofu
+
+  # random_pet.banana has changed
+  ~ resource \"random_pet\" \"banana\" {
+    }
+
+OpenTofu will perform the following actions:
+
+  # random_pet.apple will be updated in-place
+  ~ resource \"random_pet\" \"apple\" {
+      ~ length = 2 -> 3
+    }
+
+  # random_pet.banana must be replaced
+-/+ resource \"random_pet\" \"banana\" {
+      ~ length = 2 -> 3 # forces replacement
+    }
+";
+
+    #[test]
+    fn test_resource_line() {
+        let state = TextViewState::new(PLAN.to_string());
+        assert_eq!(state.resource_line("random_pet.apple"), Some(8));
+        // The drift above the planned changes is skipped
+        assert_eq!(state.resource_line("random_pet.banana"), Some(13));
+        assert_eq!(state.resource_line("random_pet.cherry"), None);
+    }
+
+    #[test]
+    fn test_resource_at_top() {
+        let mut state = TextViewState::new(PLAN.to_string());
+        assert_eq!(state.resource_at_top(), None);
+        state.scroll_to_line(10);
+        assert_eq!(state.top_line(), 10);
+        assert_eq!(state.resource_at_top(), Some("random_pet.apple"));
+        state.scroll_to_line(13);
+        assert_eq!(state.resource_at_top(), Some("random_pet.banana"));
+    }
+
+    #[test]
+    fn test_header_address() {
+        let address = TextViewState::header_address;
+        assert_eq!(
+            address("  # aws_instance.web will be updated in-place"),
+            Some("aws_instance.web")
+        );
+        assert_eq!(
+            address("  # aws_instance.web (deposed object 3f2a) will be destroyed"),
+            Some("aws_instance.web")
+        );
+        assert_eq!(address("  # aws_instance.web has changed"), None);
+        assert_eq!(address("      ~ length = 2 -> 3"), None);
+    }
+
+    #[test]
+    fn test_wrap() {
+        let mut state = TextViewState::new("short\na line that wraps\n\nend".to_string());
+        assert_eq!(state.height(), 4);
+        state.scroll_to_line(3);
+        state.wrap(Size::new(6, 10));
+        assert_eq!(state.wrap_width, 6);
+        assert_eq!(state.height(), 6);
+        // The same line stays at the top of the view
+        assert_eq!(state.top_line(), 3);
+        assert_eq!(state.offset(), 5);
+        // Any row of a wrapped line is part of that line
+        state.set_offset(3);
+        assert_eq!(state.top_line(), 1);
+        assert_eq!(state.top_line_rows_hidden(), 2);
+
+        // A column is left for the scrollbar when the plan doesn't fit
+        state.wrap(Size::new(6, 5));
+        assert_eq!(state.wrap_width, 5);
+    }
+
+    #[test]
+    fn test_scroll_to_resource() {
+        let mut state = TextViewState::new(PLAN.to_string());
+        state.scroll_to_resource(true);
+        assert_eq!(state.top_line(), 8);
+        state.scroll_to_resource(true);
+        assert_eq!(state.top_line(), 13);
+        // There's no resource after the last
+        state.scroll_to_resource(true);
+        assert_eq!(state.top_line(), 13);
+        state.scroll_to_resource(false);
+        assert_eq!(state.top_line(), 8);
+    }
+
+    #[test]
+    fn test_scroll_to_match() {
+        let mut state = TextViewState::new(PLAN.to_string());
+        state.query = "LENGTH".to_string();
+        assert_eq!(state.matching_lines(), vec![10, 15]);
+        state.scroll_to_match(true);
+        assert_eq!(state.top_line(), 10);
+        assert_eq!(state.match_position(), (Some(0), 2));
+        state.scroll_to_match(true);
+        assert_eq!(state.top_line(), 15);
+        // Searching wraps around at either end
+        state.scroll_to_match(true);
+        assert_eq!(state.top_line(), 10);
+        state.scroll_to_match(false);
+        assert_eq!(state.top_line(), 15);
+    }
+
+    #[test]
+    fn test_wrap_beyond_u16_rows() {
+        let plan = "line\n".repeat(70_000);
+        let mut state = TextViewState::new(plan);
+        state.wrap(Size::new(20, 10));
+        assert_eq!(state.height(), 70_000);
+        state.scroll_to_line(69_000);
+        assert_eq!(state.top_line(), 69_000);
+        state.process_keypress(&KeyEvent::from(KeyCode::Char('G')), &Keymap::default());
+        assert_eq!(state.offset(), 69_990);
+    }
 }
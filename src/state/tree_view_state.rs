@@ -1,14 +1,42 @@
 use ratatui::{
     crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind},
-    layout::Position,
+    layout::{Position, Rect},
 };
+use std::collections::HashSet;
+
 use tui_tree_widget::TreeState;
 
-use crate::model::trowel_diff::TrowelDiff;
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::{trowel_diff::TrowelDiff, trowel_filter::TrowelFilter, verb::Verb},
+};
 
 pub struct TreeViewState {
     pub diff: TrowelDiff,
     pub tree_state: TreeState<String>,
+    /// Occurrences of the query are highlighted, and n and N jump between the nodes containing it
+    pub query: String,
+    /// Whether keypresses are being typed into the query
+    pub editing_query: bool,
+    /// The selection when the query started being edited, which searching starts from
+    pub query_origin: Vec<String>,
+    pub filter: TrowelFilter,
+    /// Whether keypresses are being typed into a new filter expression
+    pub editing_filter: bool,
+    /// The filter expression being typed
+    pub filter_input: String,
+    /// Why the typed filter expression couldn't be used
+    pub filter_error: Option<String>,
+    /// Whether the details of the selected resource are shown beside the tree
+    pub show_detail: bool,
+    /// The resource whose details were last shown, and how far they're scrolled
+    pub detail_resource: Option<String>,
+    pub detail_scroll: usize,
+    /// Whether `z` was just pressed, so that the next digit is a depth to unfold to
+    pub awaiting_depth: bool,
+    /// Where each verb's summary was last drawn in the footer, so that clicks can be mapped to
+    /// verbs
+    pub footer_verbs: Vec<(Rect, Verb)>,
 }
 
 impl TreeViewState {
@@ -16,41 +44,394 @@ impl TreeViewState {
         TreeViewState {
             diff,
             tree_state: TreeState::default(),
+            query: String::new(),
+            editing_query: false,
+            query_origin: Vec::new(),
+            filter: TrowelFilter::default(),
+            editing_filter: false,
+            filter_input: String::new(),
+            filter_error: None,
+            show_detail: true,
+            detail_resource: None,
+            detail_scroll: 0,
+            awaiting_depth: false,
+            footer_verbs: Vec::new(),
+        }
+    }
+
+    /// Swaps in a new diff, e.g. after re-planning, keeping the selection and fold state of
+    /// whatever is still in the tree
+    pub fn replace_diff(&mut self, diff: TrowelDiff) {
+        self.diff = diff;
+        self.forget_missing_nodes();
+    }
+
+    /// Drops fold state for nodes that no longer exist, and moves the selection to its nearest
+    /// ancestor if it is no longer shown, e.g. because it was filtered out
+    fn forget_missing_nodes(&mut self) {
+        let paths = |filter: &TrowelFilter| -> HashSet<Vec<String>> {
+            self.diff
+                .search("", filter)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect()
+        };
+        let existing = paths(&TrowelFilter::default());
+        let shown = paths(&self.filter);
+
+        let mut tree_state = TreeState::default();
+        for path in self.tree_state.opened() {
+            if existing.contains(path) {
+                tree_state.open(path.clone());
+            }
+        }
+        // A selected attribute may no longer be changed, in which case its resource is selected
+        let mut selected = self.tree_state.selected().to_vec();
+        while !selected.is_empty() && !shown.contains(&selected) {
+            selected.pop();
+        }
+        tree_state.select(selected);
+
+        self.tree_state = tree_state;
+    }
+
+    /// Unfolds every node down to a depth, where resources are at depth 1, and folds the rest
+    fn open_to_depth(&mut self, depth: usize) {
+        let nodes = self.diff.search("", &TrowelFilter::default());
+        self.tree_state.close_all();
+        for (i, (path, _)) in nodes.iter().enumerate() {
+            // Nodes are listed in tree order, so a node's children come straight after it
+            let has_children = nodes
+                .get(i + 1)
+                .is_some_and(|(next, _)| next.len() > path.len());
+            if has_children && path.len() <= depth {
+                self.tree_state.open(path.clone());
+            }
+        }
+        // A selection that has been folded away moves to the node it was folded into
+        let mut selected = self.tree_state.selected().to_vec();
+        if selected.len() > depth.saturating_add(1) {
+            selected.truncate(depth + 1);
+            self.tree_state.select(selected);
+        }
+    }
+
+    /// Whether keypresses are being typed as text rather than used as commands
+    pub fn is_editing(&self) -> bool {
+        self.editing_query || self.editing_filter
+    }
+
+    pub fn process_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        if self.editing_query {
+            self.process_query_keypress(key, keymap);
+            return;
+        }
+        if self.editing_filter {
+            self.process_filter_keypress(key, keymap);
+            return;
+        }
+        if self.awaiting_depth {
+            self.awaiting_depth = false;
+            if let KeyCode::Char(c) = key.code
+                && let Some(depth) = c.to_digit(10)
+            {
+                self.open_to_depth(depth as usize);
+            }
+            return;
+        }
+        let Some(action) = keymap.action(KeyContext::Tree, key) else {
+            return;
+        };
+        if let Some(verb) = action.toggled_verb() {
+            self.toggle_verb(&verb);
+            return;
+        }
+        match action {
+            // Fold and unfold everything
+            Action::OpenAll => self.open_to_depth(usize::MAX),
+            Action::CloseAll => self.open_to_depth(0),
+            Action::OpenToDepth => self.awaiting_depth = true,
+
+            // Search
+            Action::Search => {
+                self.editing_query = true;
+                self.query_origin = self.tree_state.selected().to_vec();
+                self.query.clear();
+            }
+            Action::NextMatch => self.select_match(true),
+            Action::PreviousMatch => self.select_match(false),
+            Action::ClearSearch => self.query.clear(),
+
+            // Details
+            Action::ToggleDetail => self.show_detail = !self.show_detail,
+            Action::ScrollDetailDown => self.detail_scroll = self.detail_scroll.saturating_add(1),
+            Action::ScrollDetailUp => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+
+            // Filter
+            Action::Filter => {
+                self.editing_filter = true;
+                self.filter_input = self.filter.expression.clone();
+            }
+            Action::ShowAllActions => self.set_filter(TrowelFilter::default()),
+            _ => {
+                Self::navigate(&mut self.tree_state, action);
+            }
+        }
+    }
+
+    fn process_query_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        match (keymap.action(KeyContext::Typing, key), key.code) {
+            (Some(Action::FinishTyping), _) => self.editing_query = false,
+            (Some(Action::CancelTyping), _) => {
+                self.editing_query = false;
+                self.query.clear();
+                self.tree_state.select(self.query_origin.clone());
+            }
+            (_, KeyCode::Char(c)) => {
+                self.query.push(c);
+                self.select_first_match();
+            }
+            (_, KeyCode::Backspace) => {
+                self.query.pop();
+                self.select_first_match();
+            }
+            _ => (),
+        }
+    }
+
+    fn process_filter_keypress(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        match (keymap.action(KeyContext::Typing, key), key.code) {
+            (Some(Action::FinishTyping), _) => {
+                let mut filter = self.filter.clone();
+                match filter.set_expression(&self.filter_input) {
+                    Ok(()) => {
+                        self.editing_filter = false;
+                        self.set_filter(filter);
+                    }
+                    Err(message) => self.filter_error = Some(message),
+                }
+            }
+            (Some(Action::CancelTyping), _) => {
+                self.editing_filter = false;
+                self.filter_error = None;
+            }
+            (_, KeyCode::Char(c)) => {
+                self.filter_input.push(c);
+                self.filter_error = None;
+            }
+            (_, KeyCode::Backspace) => {
+                self.filter_input.pop();
+                self.filter_error = None;
+            }
+            _ => (),
+        }
+    }
+
+    /// The position of the selected node among those matching the query, and how many match
+    pub fn match_position(&self) -> (Option<usize>, usize) {
+        let matches: Vec<_> = self
+            .diff
+            .search(&self.query, &self.filter)
+            .into_iter()
+            .filter(|(_, matches)| *matches)
+            .collect();
+        let selected = self.tree_state.selected();
+        let position = matches.iter().position(|(path, _)| path == selected);
+        (position, matches.len())
+    }
+
+    /// Selects the first match from where the search started, as the query is typed
+    fn select_first_match(&mut self) {
+        let nodes = self.diff.search(&self.query, &self.filter);
+        let start = nodes
+            .iter()
+            .position(|(path, _)| *path == self.query_origin)
+            .unwrap_or(0);
+        match Self::find_match(&nodes, start, true) {
+            Some(path) => self.select(path),
+            None => {
+                self.tree_state.select(self.query_origin.clone());
+            }
         }
     }
 
-    pub fn process_keypress(&mut self, key: &KeyEvent) {
-        match key.code {
+    /// Selects the next or previous match after the selection, wrapping around at the ends
+    fn select_match(&mut self, forward: bool) {
+        let nodes = self.diff.search(&self.query, &self.filter);
+        let selected = nodes
+            .iter()
+            .position(|(path, _)| path == self.tree_state.selected());
+        let start = match (selected, forward) {
+            (Some(i), true) => i + 1,
+            (Some(i), false) => (i + nodes.len() - 1) % nodes.len(),
+            (None, true) => 0,
+            (None, false) => nodes.len().saturating_sub(1),
+        };
+        if let Some(path) = Self::find_match(&nodes, start, forward) {
+            self.select(path);
+        }
+    }
+
+    /// Finds the first matching node from the start, going round in either direction
+    fn find_match(
+        nodes: &[(Vec<String>, bool)],
+        start: usize,
+        forward: bool,
+    ) -> Option<Vec<String>> {
+        let len = nodes.len();
+        (0..len)
+            .map(|i| {
+                if forward {
+                    (start + i) % len
+                } else {
+                    (start + len - i) % len
+                }
+            })
+            .filter_map(|i| nodes.get(i))
+            .find(|(_, matches)| *matches)
+            .map(|(path, _)| path.clone())
+    }
+
+    /// Selects a resource unless it, or one of its attributes, is already selected, e.g. to
+    /// follow the text view. Resources hidden by the filter are left alone.
+    pub fn select_resource(&mut self, address: &str) {
+        let shown = self
+            .diff
+            .entry(address)
+            .is_some_and(|e| self.filter.matches(e));
+        if shown && self.tree_state.selected().first().map(String::as_str) != Some(address) {
+            self.tree_state.select(vec![address.to_string()]);
+        }
+    }
+
+    /// Selects a node, unfolding everything above it so that it can be seen
+    fn select(&mut self, path: Vec<String>) {
+        let mut ancestor = Vec::new();
+        for identifier in path.iter().take(path.len().saturating_sub(1)) {
+            ancestor.push(identifier.clone());
+            self.tree_state.open(ancestor.clone());
+        }
+        self.tree_state.select(path);
+    }
+
+    pub fn process_mouse_event(&mut self, mouse: MouseEvent) {
+        if let MouseEventKind::Down(_) = mouse.kind
+            && let Some(verb) = self.footer_verb_at(mouse.column, mouse.row)
+        {
+            self.toggle_verb(&verb);
+            return;
+        }
+        Self::navigate_with_mouse(&mut self.tree_state, mouse);
+    }
+
+    fn toggle_verb(&mut self, verb: &Verb) {
+        let mut filter = self.filter.clone();
+        filter.toggle_verb(verb);
+        self.set_filter(filter);
+    }
+
+    fn set_filter(&mut self, filter: TrowelFilter) {
+        self.filter = filter;
+        self.forget_missing_nodes();
+    }
+
+    /// The verb whose summary was drawn in the footer at a position on screen
+    fn footer_verb_at(&self, column: u16, row: u16) -> Option<Verb> {
+        self.footer_verbs
+            .iter()
+            .find(|(area, _)| area.contains(Position::new(column, row)))
+            .map(|(_, verb)| verb.clone())
+    }
+
+    /// Moves around a tree, returning whether anything changed. Shared with other views built on trees.
+    pub fn navigate(tree_state: &mut TreeState<String>, action: Action) -> bool {
+        match action {
             // Fold and unfold
-            KeyCode::Enter => self.tree_state.toggle_selected(),
+            Action::ToggleFold => tree_state.toggle_selected(),
 
             // Basic navigation
-            KeyCode::Char('h') => self.tree_state.key_left(),
-            KeyCode::Char('l') => self.tree_state.key_right(),
-            KeyCode::Char('j') => self.tree_state.key_down(),
-            KeyCode::Char('k') => self.tree_state.key_up(),
-            KeyCode::Left => self.tree_state.key_left(),
-            KeyCode::Right => self.tree_state.key_right(),
-            KeyCode::Down => self.tree_state.key_down(),
-            KeyCode::Up => self.tree_state.key_up(),
+            Action::Left => tree_state.key_left(),
+            Action::Right => tree_state.key_right(),
+            Action::Down => tree_state.key_down(),
+            Action::Up => tree_state.key_up(),
 
             // Jump to top and bottom
-            KeyCode::Char('g') => self.tree_state.select_first(),
-            KeyCode::Char('G') => self.tree_state.select_last(),
-            KeyCode::Home => self.tree_state.select_first(),
-            KeyCode::End => self.tree_state.select_last(),
+            Action::Top => tree_state.select_first(),
+            Action::Bottom => tree_state.select_last(),
             _ => false,
-        };
+        }
     }
 
-    pub fn process_mouse_event(&mut self, mouse: MouseEvent) {
+    pub fn navigate_with_mouse(tree_state: &mut TreeState<String>, mouse: MouseEvent) -> bool {
         match mouse.kind {
-            MouseEventKind::ScrollDown => self.tree_state.scroll_down(1),
-            MouseEventKind::ScrollUp => self.tree_state.scroll_up(1),
-            MouseEventKind::Down(_button) => self
-                .tree_state
-                .click_at(Position::new(mouse.column, mouse.row)),
+            MouseEventKind::ScrollDown => tree_state.scroll_down(1),
+            MouseEventKind::ScrollUp => tree_state.scroll_up(1),
+            MouseEventKind::Down(_button) => {
+                tree_state.click_at(Position::new(mouse.column, mouse.row))
+            }
             _ => false,
-        };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::tf_plan::TfPlan;
+
+    use super::*;
+
+    fn state() -> TreeViewState {
+        TreeViewState::new(TrowelDiff::test(vec![
+            TfPlan::test_change("apple", "update"),
+            TfPlan::test_change("banana", "update"),
+        ]))
+    }
+
+    #[test]
+    fn test_open_to_depth() {
+        let mut state = state();
+        state.open_to_depth(usize::MAX);
+        let mut opened: Vec<_> = state.tree_state.opened().iter().cloned().collect();
+        opened.sort();
+        // Only nodes with children are opened
+        assert_eq!(opened, vec![vec!["apple"], vec!["banana"]]);
+
+        state
+            .tree_state
+            .select(vec!["banana".to_string(), "banana length".to_string()]);
+        state.open_to_depth(0);
+        assert!(state.tree_state.opened().is_empty());
+        assert_eq!(state.tree_state.selected(), ["banana"]);
+    }
+
+    #[test]
+    fn test_select_resource() {
+        let mut state = state();
+        state
+            .tree_state
+            .select(vec!["apple".to_string(), "apple length".to_string()]);
+        // Selecting the resource that's already selected keeps the attribute selected
+        state.select_resource("apple");
+        assert_eq!(state.tree_state.selected(), ["apple", "apple length"]);
+        state.select_resource("banana");
+        assert_eq!(state.tree_state.selected(), ["banana"]);
+        state.select_resource("cherry");
+        assert_eq!(state.tree_state.selected(), ["banana"]);
+    }
+
+    #[test]
+    fn test_find_match() {
+        let nodes: Vec<(Vec<String>, bool)> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|n| (vec![n.to_string()], *n == "a" || *n == "c"))
+            .collect();
+        let found = |start, forward| TreeViewState::find_match(&nodes, start, forward);
+        assert_eq!(found(0, true), Some(vec!["a".to_string()]));
+        assert_eq!(found(1, true), Some(vec!["c".to_string()]));
+        // Searching wraps around at either end
+        assert_eq!(found(3, true), Some(vec!["a".to_string()]));
+        assert_eq!(found(1, false), Some(vec!["a".to_string()]));
+        assert_eq!(found(0, false), Some(vec!["a".to_string()]));
+        assert_eq!(TreeViewState::find_match(&[], 0, true), None);
     }
 }
@@ -0,0 +1,148 @@
+use ratatui::{crossterm::event::KeyEvent, widgets::ListState};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::trowel_diff::TrowelDiff,
+};
+
+#[derive(Clone)]
+pub struct PlannedWorkspace {
+    pub diff: TrowelDiff,
+    pub text_plan: Option<String>,
+}
+
+pub enum WorkspacePlan {
+    NotPlanned,
+    Queued,
+    Planning,
+    Planned(PlannedWorkspace),
+    Failed(String),
+}
+
+pub struct Workspace {
+    pub name: String,
+    pub plan: WorkspacePlan,
+}
+
+pub struct WorkspaceViewState {
+    pub workspaces: Vec<Workspace>,
+    /// The workspace selected in the working directory
+    pub current: String,
+    /// The workspace whose plan is shown in the tree and text views
+    pub displayed: String,
+    pub list_state: ListState,
+}
+
+impl WorkspaceViewState {
+    pub fn new(names: Vec<String>, current: String, current_plan: PlannedWorkspace) -> Self {
+        let workspaces: Vec<Workspace> = names
+            .into_iter()
+            .map(|name| {
+                let plan = if name == current {
+                    WorkspacePlan::Planned(current_plan.clone())
+                } else {
+                    WorkspacePlan::NotPlanned
+                };
+                Workspace { name, plan }
+            })
+            .collect();
+        let selected = workspaces.iter().position(|w| w.name == current);
+        Self {
+            workspaces,
+            displayed: current.clone(),
+            current,
+            list_state: ListState::default().with_selected(selected),
+        }
+    }
+
+    /// Handles a keypress, returning a plan when the user has chosen to display one
+    pub fn process_keypress(
+        &mut self,
+        key: &KeyEvent,
+        keymap: &Keymap,
+    ) -> Option<PlannedWorkspace> {
+        match keymap.action(KeyContext::Workspaces, key)? {
+            // Basic navigation
+            Action::Down => self.list_state.select_next(),
+            Action::Up => self.list_state.select_previous(),
+
+            // Display the selected workspace, planning it first if needed
+            Action::DisplayWorkspace => return self.display_selected(),
+
+            // Plan the selected workspace again, or every unplanned workspace
+            Action::Replan => {
+                if let Some(workspace) = self.selected_mut() {
+                    Self::enqueue(workspace, true);
+                }
+            }
+            Action::PlanAll => {
+                for workspace in &mut self.workspaces {
+                    Self::enqueue(workspace, false);
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+
+    /// Takes the next workspace waiting to be planned, marking it as in progress
+    pub fn start_next_plan(&mut self) -> Option<String> {
+        let next = self
+            .workspaces
+            .iter_mut()
+            .find(|w| matches!(w.plan, WorkspacePlan::Queued))?;
+        next.plan = WorkspacePlan::Planning;
+        Some(next.name.clone())
+    }
+
+    /// Records the result of a plan, returning it if it replaces the one being displayed
+    pub fn finish_plan(
+        &mut self,
+        name: &str,
+        result: Result<PlannedWorkspace, String>,
+    ) -> Option<PlannedWorkspace> {
+        let workspace = self.workspaces.iter_mut().find(|w| w.name == name)?;
+        match result {
+            Ok(planned) => {
+                workspace.plan = WorkspacePlan::Planned(planned.clone());
+                (self.displayed == name).then_some(planned)
+            }
+            Err(message) => {
+                workspace.plan = WorkspacePlan::Failed(message);
+                None
+            }
+        }
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut Workspace> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.workspaces.get_mut(i))
+    }
+
+    fn display_selected(&mut self) -> Option<PlannedWorkspace> {
+        let workspace = self.selected_mut()?;
+        match &workspace.plan {
+            WorkspacePlan::Planned(planned) => {
+                let planned = planned.clone();
+                let name = workspace.name.clone();
+                self.displayed = name;
+                Some(planned)
+            }
+            _ => {
+                Self::enqueue(workspace, false);
+                None
+            }
+        }
+    }
+
+    fn enqueue(workspace: &mut Workspace, replan: bool) {
+        match workspace.plan {
+            WorkspacePlan::NotPlanned | WorkspacePlan::Failed(_) => {
+                workspace.plan = WorkspacePlan::Queued
+            }
+            WorkspacePlan::Planned(_) if replan => workspace.plan = WorkspacePlan::Queued,
+            _ => (),
+        }
+    }
+}
@@ -1,62 +1,216 @@
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use tempfile::NamedTempFile;
+use nix::{
+    sys::signal::{self, Signal},
+    unistd::Pid,
+};
 use tokio::{
     io::{AsyncBufReadExt, AsyncReadExt, BufReader},
     sync::mpsc,
 };
 
+use crate::model::{
+    tf_event::{TfEvent, TfEventBody, TfEventDiagnostic},
+    tf_version::{TfFlavour, TfVersion, TfVersionJson},
+};
+
+/// How a long-running command such as `plan` or `init` ended
+pub enum RunOutcome {
+    Succeeded,
+    Failed(Vec<TfEventDiagnostic>),
+    Interrupted,
+}
+
 #[derive(Clone)]
 pub struct TfClient {
     binary: String,
+    version: Option<TfVersion>,
+    workspace: Option<String>,
 }
 
 impl TfClient {
     pub fn new(binary: String) -> Self {
-        Self { binary }
+        Self {
+            binary,
+            version: None,
+            workspace: None,
+        }
     }
 
-    pub async fn plan(&self, tx: mpsc::Sender<String>) -> Result<NamedTempFile, io::Error> {
-        let tempfile = NamedTempFile::new()?;
-        let mut child = tokio::process::Command::new(&self.binary)
-            .arg("plan")
-            .arg("-no-color")
-            .arg("-out")
-            .arg(tempfile.path())
+    /// A client whose commands run against the given workspace, without changing the selected one
+    pub fn in_workspace(&self, workspace: &str) -> Self {
+        Self {
+            workspace: Some(workspace.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Creates a client after checking that the binary is a supported version of OpenTofu or Terraform
+    pub async fn detect(binary: String) -> Result<Self, io::Error> {
+        let mut client = Self::new(binary);
+        let version = client.version_info().await?;
+        version.check_supported()?;
+        client.version = Some(version);
+        Ok(client)
+    }
+
+    pub fn version(&self) -> Option<&TfVersion> {
+        self.version.as_ref()
+    }
+
+    async fn version_info(&self) -> Result<TfVersion, io::Error> {
+        // Both flavours report "terraform_version" in JSON, so only the text output tells them apart
+        let text = self.run_to_string(&["version"]).await?;
+        let flavour = TfFlavour::from_version_text(&text)?;
+        let json = self.run_to_string(&["version", "-json"]).await?;
+        let parsed: TfVersionJson = serde_json::from_str(&json)?;
+        TfVersion::new(flavour, &parsed)
+    }
+
+    /// Lists all workspaces along with the currently selected one
+    pub async fn workspaces(&self) -> Result<(Vec<String>, String), io::Error> {
+        let list = self.run_to_string(&["workspace", "list"]).await?;
+        let (workspaces, current) = parse_workspace_list(&list);
+        match current {
+            Some(current) => Ok((workspaces, current)),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "No workspace is marked as selected in workspace list output",
+            )),
+        }
+    }
+
+    fn command(&self) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.binary);
+        if let Some(workspace) = &self.workspace {
+            command.env("TF_WORKSPACE", workspace);
+        }
+        command
+    }
+
+    async fn run_to_string(&self, args: &[&str]) -> Result<String, io::Error> {
+        let output = self.command().args(args).output().await.map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to run {}: {}", self.binary, e))
+        })?;
+        String::from_utf8(output.stdout).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to parse output of {:?} into UTF-8", args),
+            )
+        })
+    }
+
+    pub async fn plan(
+        &self,
+        out: &Path,
+        tx: mpsc::Sender<TfEvent>,
+        interrupt_rx: mpsc::Receiver<()>,
+    ) -> Result<RunOutcome, io::Error> {
+        // Every supported version streams events from `plan -json`
+        let mut command = self.command();
+        command.arg("plan").arg("-json").arg("-out").arg(out);
+        self.run_streaming(command, true, tx, interrupt_rx).await
+    }
+
+    /// Initializes the working directory, e.g. after a plan failed because it wasn't initialized
+    pub async fn init(
+        &self,
+        tx: mpsc::Sender<TfEvent>,
+        interrupt_rx: mpsc::Receiver<()>,
+    ) -> Result<RunOutcome, io::Error> {
+        // Versions are only unknown when detection was skipped, in which case assume a modern binary
+        let streaming = self
+            .version
+            .as_ref()
+            .is_none_or(TfVersion::supports_json_init_streaming);
+        let mut command = self.command();
+        command
+            .arg("init")
+            .arg(if streaming { "-json" } else { "-no-color" })
+            // There is nobody to answer prompts, e.g. about migrating state between backends
+            .arg("-input=false")
+            .stdin(std::process::Stdio::null());
+        self.run_streaming(command, streaming, tx, interrupt_rx)
+            .await
+    }
+
+    /// Runs a command while forwarding its output as events, and interrupts from the receiver as SIGINT
+    async fn run_streaming(
+        &self,
+        mut command: tokio::process::Command,
+        streaming: bool,
+        tx: mpsc::Sender<TfEvent>,
+        mut interrupt_rx: mpsc::Receiver<()>,
+    ) -> Result<RunOutcome, io::Error> {
+        let mut child = command
             .stderr(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .spawn()?;
 
         let stdout = child.stdout.take().ok_or(io::Error::new(
             io::ErrorKind::NotFound,
-            "Failed to take stdout for plan process",
+            format!("Failed to take stdout for {} process", self.binary),
         ))?;
         let mut reader = BufReader::new(stdout).lines();
-        while let Some(line) = reader.next_line().await? {
-            tx.send(line).await.ok();
+        let mut diagnostics = Vec::new();
+        let mut interrupted = false;
+        loop {
+            tokio::select! {
+                line = reader.next_line() => {
+                    let Some(line) = line? else { break };
+                    let event = if streaming {
+                        TfEvent::from_line(&line).ok()
+                    } else {
+                        Some(TfEvent::from_text(line))
+                    };
+                    // Lines that aren't machine-readable events carry nothing worth showing
+                    if let Some(event) = event {
+                        if let TfEventBody::Diagnostic { diagnostic } = &event.body {
+                            diagnostics.push(diagnostic.clone());
+                        }
+                        tx.send(event).await.ok();
+                    }
+                }
+                Some(()) = interrupt_rx.recv() => {
+                    // The child keeps running until it has shut down gracefully and released any state lock
+                    interrupted = true;
+                    if let Some(pid) = child.id() {
+                        signal::kill(Pid::from_raw(pid as i32), Signal::SIGINT)?;
+                    }
+                }
+            }
         }
 
         let status = child.wait().await?;
-        if status.success() {
-            Ok(tempfile)
+        if interrupted {
+            Ok(RunOutcome::Interrupted)
+        } else if status.success() {
+            Ok(RunOutcome::Succeeded)
         } else {
             let mut stderr = child.stderr.take().ok_or(io::Error::new(
                 io::ErrorKind::NotFound,
-                "Failed to take stderr for plan process",
+                format!("Failed to take stderr for {} process", self.binary),
             ))?;
+            // With -json, errors are usually reported as diagnostics on stdout, but anything
+            // written to stderr (e.g. a crash) is kept as a diagnostic of its own
             let mut buf = String::new();
             stderr.read_to_string(&mut buf).await?;
-            Err(io::Error::new(io::ErrorKind::Other, buf))
+            if !buf.trim().is_empty() {
+                diagnostics.push(TfEventDiagnostic::from_stderr(buf));
+            }
+            Ok(RunOutcome::Failed(diagnostics))
         }
     }
 
-    pub fn show_as_json(&self, binary_plan_file: &PathBuf) -> Result<String, io::Error> {
-        let output = std::process::Command::new(&self.binary)
+    pub async fn show_as_json(&self, binary_plan_file: &PathBuf) -> Result<String, io::Error> {
+        let output = self
+            .command()
             .arg("show")
             .arg("-json")
             .arg(binary_plan_file)
-            .output()?;
+            .output()
+            .await?;
         match std::str::from_utf8(&output.stdout) {
             Ok(out) => Ok(out.to_owned()),
             Err(_) => Err(io::Error::new(
@@ -66,12 +220,31 @@ impl TfClient {
         }
     }
 
-    pub fn show_as_text(&self, binary_plan_file: &PathBuf) -> Result<String, io::Error> {
-        let output = std::process::Command::new(&self.binary)
+    /// Shows the current state, which requires an initialized working directory
+    pub async fn show_state_as_json(&self) -> Result<String, io::Error> {
+        let output = self.command().arg("show").arg("-json").output().await?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Failed to show state: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Failed to parse JSON state stdout into UTF-8",
+            )
+        })
+    }
+
+    pub async fn show_as_text(&self, binary_plan_file: &PathBuf) -> Result<String, io::Error> {
+        let output = self
+            .command()
             .arg("show")
             .arg("-no-color")
             .arg(binary_plan_file)
-            .output()?;
+            .output()
+            .await?;
         match std::str::from_utf8(&output.stdout) {
             Ok(out) => Ok(out.to_owned()),
             Err(_) => Err(io::Error::new(
@@ -81,3 +254,42 @@ impl TfClient {
         }
     }
 }
+
+/// Parses `workspace list` output, where the selected workspace is prefixed with an asterisk
+fn parse_workspace_list(output: &str) -> (Vec<String>, Option<String>) {
+    let mut workspaces = Vec::new();
+    let mut current = None;
+    for line in output.lines() {
+        let line = line.trim();
+        let name = match line.strip_prefix('*') {
+            Some(name) => {
+                current = Some(name.trim().to_string());
+                name.trim()
+            }
+            None => line,
+        };
+        if !name.is_empty() {
+            workspaces.push(name.to_string());
+        }
+    }
+    (workspaces, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_workspace_list() {
+        let (workspaces, current) = parse_workspace_list("  default\n* staging\n  prod\n\n");
+        assert_eq!(workspaces, vec!["default", "staging", "prod"]);
+        assert_eq!(current, Some("staging".to_string()));
+    }
+
+    #[test]
+    fn test_parse_workspace_list_empty() {
+        let (workspaces, current) = parse_workspace_list("");
+        assert!(workspaces.is_empty());
+        assert_eq!(current, None);
+    }
+}
@@ -6,14 +6,14 @@ use ratatui::{
     widgets::{Block, BorderType, Paragraph, StatefulWidget, Widget},
 };
 
-use crate::state::app_state::{ActiveView, AppState};
+use crate::state::app_state::{ActiveView, AppState, WatchStatus};
 
-use super::{error_view::ErrorView, text_view::TextView, tree_view::TreeView};
+use super::{
+    detail_view::DetailView, error_view::ErrorView, help_view::HelpView, text_view::TextView,
+    tree_view::TreeView, workspace_view::WorkspaceView,
+};
 
-pub struct AppView {
-    text_view: TextView,
-    tree_view: TreeView,
-}
+pub struct AppView {}
 
 impl StatefulWidget for AppView {
     type State = AppState;
@@ -29,28 +29,53 @@ impl StatefulWidget for AppView {
             Self::experimental_warning().render(a1, buf);
         }
 
+        let area = match Self::status_line(state) {
+            Some(line) => {
+                let (status_area, area) = Self::status_layout(area);
+                line.render(status_area, buf);
+                area
+            }
+            None => area,
+        };
+
+        let keymap = &state.keymap;
         match state.active_view {
+            ActiveView::TreeView if state.tree_view_state.show_detail => {
+                let (tree_area, detail_area) = Self::detail_layout(area);
+                TreeView::new(keymap).render(tree_area, buf, &mut state.tree_view_state);
+                DetailView::new(keymap).render(detail_area, buf, &mut state.tree_view_state);
+            }
             ActiveView::TreeView => {
-                self.tree_view.render(area, buf, &mut state.tree_view_state);
+                TreeView::new(keymap).render(area, buf, &mut state.tree_view_state);
             }
             ActiveView::TextView => match state.text_view_state.as_mut() {
-                Some(view) => self.text_view.render(area, buf, view),
+                Some(view) => TextView::new(keymap).render(area, buf, view),
                 None => ErrorView::new(
                     "No text plan available!\nYou are likely viewing a JSON plan.".to_string(),
                     Color::Yellow,
                 )
                 .render(area, buf),
             },
+            ActiveView::WorkspaceView => match state.workspace_view_state.as_mut() {
+                Some(view) => WorkspaceView::new(keymap).render(area, buf, view),
+                None => ErrorView::new("No workspaces available!".to_string(), Color::Yellow)
+                    .render(area, buf),
+            },
+        }
+
+        if state.help.shown {
+            HelpView::new(&state.keymap, state.key_context(), true).render(
+                area,
+                buf,
+                &mut state.help,
+            );
         }
     }
 }
 
 impl AppView {
     pub fn new() -> Self {
-        Self {
-            text_view: TextView::new(),
-            tree_view: TreeView::new(),
-        }
+        Self {}
     }
 
     fn experimental_warning() -> impl Widget {
@@ -65,6 +90,78 @@ impl AppView {
         Paragraph::new(text).block(block).centered()
     }
 
+    /// A row above the views describing whether the plan is being kept up to date, the
+    /// displayed workspace and the binary that planned it
+    fn status_line(state: &AppState) -> Option<Line<'static>> {
+        let watch = state.watch_status.as_ref().map(|status| match status {
+            WatchStatus::Watching => Span::styled("watching", Style::default().fg(Color::DarkGray)),
+            WatchStatus::Replanning => {
+                Span::styled("re-planning...", Style::default().fg(Color::Yellow))
+            }
+            WatchStatus::Failed(message) => Span::styled(
+                format!("re-plan failed: {}", message),
+                Style::default().fg(Color::Red),
+            ),
+        });
+        let workspace = state
+            .workspace_view_state
+            .as_ref()
+            .map(|w| format!("workspace: {}", w.displayed));
+        let version = state.tf_version.as_ref().map(|v| v.to_string());
+        let spans: Vec<_> = watch
+            .into_iter()
+            .chain(
+                [workspace, version]
+                    .into_iter()
+                    .flatten()
+                    .map(|text| Span::styled(text, Style::default().fg(Color::DarkGray))),
+            )
+            .collect();
+        if spans.is_empty() {
+            return None;
+        }
+        let separator = Span::styled(" | ", Style::default().fg(Color::DarkGray));
+        let mut line = Vec::new();
+        for (i, span) in spans.into_iter().enumerate() {
+            if i > 0 {
+                line.push(separator.clone());
+            }
+            line.push(span);
+        }
+        line.push(Span::from(" "));
+        Some(Line::from(line).right_aligned())
+    }
+
+    /// Puts the details beside the tree, or below it on narrow terminals
+    fn detail_layout(area: Rect) -> (Rect, Rect) {
+        const SIDE_BY_SIDE_MIN_WIDTH: u16 = 120;
+
+        let (direction, constraints) = if area.width >= SIDE_BY_SIDE_MIN_WIDTH {
+            (
+                Direction::Horizontal,
+                [Constraint::Percentage(55), Constraint::Percentage(45)],
+            )
+        } else {
+            (
+                Direction::Vertical,
+                [Constraint::Percentage(60), Constraint::Percentage(40)],
+            )
+        };
+        let [a1, a2] = Layout::default()
+            .direction(direction)
+            .constraints(constraints)
+            .areas(area);
+        (a1, a2)
+    }
+
+    fn status_layout(area: Rect) -> (Rect, Rect) {
+        let [a1, a2] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1), Constraint::Min(1)])
+            .areas(area);
+        (a1, a2)
+    }
+
     fn experimental_warning_layout(area: Rect) -> (Rect, Rect) {
         let [a1, a2] = Layout::default()
             .direction(Direction::Vertical)
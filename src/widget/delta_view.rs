@@ -0,0 +1,70 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::Span,
+    widgets::{Block, Scrollbar, ScrollbarOrientation, StatefulWidget, Widget},
+};
+use tui_tree_widget::Tree;
+
+use crate::state::delta_view_state::DeltaViewState;
+
+use super::error_view::ErrorView;
+
+pub struct DeltaView {}
+
+impl StatefulWidget for DeltaView {
+    type State = DeltaViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if state.delta.is_empty() {
+            ErrorView::new(
+                "Both plans make exactly the same changes".to_string(),
+                Color::Green,
+            )
+            .render(area, buf);
+            return;
+        }
+        let Ok(tree_items) = state.delta.to_tree_items() else {
+            ErrorView::new(
+                "Failed to parse plan comparison into tree items! This should not be possible."
+                    .to_string(),
+                Color::Red,
+            )
+            .render(area, buf);
+            return;
+        };
+        let block = Block::bordered()
+            .title(Self::title())
+            .title_bottom(state.delta.kind_uses_fmt());
+        if let Ok(tree) = Tree::new(&tree_items) {
+            let style = Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD);
+            let tree = tree
+                .block(block)
+                .experimental_scrollbar(Some(Self::scrollbar()))
+                .highlight_style(style);
+            StatefulWidget::render(tree, area, buf, &mut state.tree_state);
+        }
+    }
+}
+
+impl DeltaView {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn title() -> Span<'static> {
+        Span::styled(" Plan comparison ", Style::default().fg(Color::Blue))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    fn scrollbar() -> Scrollbar<'static> {
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .track_symbol(None)
+            .end_symbol(None)
+    }
+}
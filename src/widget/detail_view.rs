@@ -0,0 +1,222 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, StatefulWidget, Widget, Wrap},
+};
+use serde_json::Value;
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::trowel_diff::TrowelDiffEntry,
+    state::tree_view_state::TreeViewState,
+};
+
+/// Everything about the resource selected in the tree, with values in full
+pub struct DetailView<'a> {
+    keymap: &'a Keymap,
+}
+
+impl StatefulWidget for DetailView<'_> {
+    type State = TreeViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let hint = self.keymap.hint(
+            KeyContext::Tree,
+            &[
+                (
+                    &[Action::ScrollDetailDown, Action::ScrollDetailUp],
+                    "scroll",
+                ),
+                (&[Action::ToggleDetail], "hide"),
+            ],
+        );
+        let block = Block::bordered()
+            .title(Self::title())
+            .title_bottom(Line::from(format!(" {} ", hint)).right_aligned());
+        let selected = state.tree_state.selected().first().cloned();
+        let Some(entry) = selected.as_deref().and_then(|path| state.diff.entry(path)) else {
+            Paragraph::new(Line::styled(
+                "Select a resource to see its details",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block)
+            .render(area, buf);
+            return;
+        };
+
+        // Each resource is shown from the top
+        if state.detail_resource.as_ref() != Some(&entry.resource_path) {
+            state.detail_resource = Some(entry.resource_path.clone());
+            state.detail_scroll = 0;
+        }
+        // Long values wrap, so scrolling stops once the last wrapped row is at the bottom
+        let inner = block.inner(area);
+        let paragraph = Paragraph::new(Self::lines(entry)).wrap(Wrap { trim: false });
+        let rows = paragraph.line_count(inner.width);
+        state.detail_scroll = state
+            .detail_scroll
+            .min(rows.saturating_sub(usize::from(inner.height)));
+        let scroll = u16::try_from(state.detail_scroll).unwrap_or(u16::MAX);
+        paragraph.block(block).scroll((scroll, 0)).render(area, buf);
+    }
+}
+
+impl<'a> DetailView<'a> {
+    pub fn new(keymap: &'a Keymap) -> Self {
+        Self { keymap }
+    }
+
+    fn title() -> Span<'static> {
+        Span::styled(" Details ", Style::default().fg(Color::Blue)).add_modifier(Modifier::BOLD)
+    }
+
+    fn lines(entry: &TrowelDiffEntry) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(Span::styled(
+                entry.resource_path.clone(),
+                Style::default()
+                    .fg(entry.verb.to_color())
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(format!("will be {}", entry.verb.to_past_tense())),
+            Line::default(),
+            Self::field("Type", &entry.resource_type),
+            Self::field("Provider", &entry.provider),
+            Self::field("Module", entry.module.as_deref().unwrap_or("(root module)")),
+        ];
+        if let Some(reason) = &entry.action_reason {
+            lines.push(Self::field("Reason", reason));
+        }
+        if !entry.replace_paths.is_empty() {
+            let paths: Vec<_> = entry
+                .replace_paths
+                .iter()
+                .map(|p| Self::attribute_path(p))
+                .collect();
+            lines.push(Self::field("Replaced by", &paths.join(", ")));
+        }
+
+        for (name, value) in entry.values_sorted() {
+            lines.push(Line::default());
+            let mut heading = vec![Span::styled(
+                name.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )];
+            if value.changed() {
+                lines.push(Line::from(heading));
+                Self::push_value(
+                    &mut lines,
+                    "- ",
+                    Color::Red,
+                    &value.before_pretty(),
+                    value.before_style(),
+                );
+                Self::push_value(
+                    &mut lines,
+                    "+ ",
+                    Color::Green,
+                    &value.after_pretty(),
+                    value.after_style(),
+                );
+            } else {
+                heading.push(Span::styled(
+                    " (unchanged)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+                lines.push(Line::from(heading));
+                Self::push_value(
+                    &mut lines,
+                    "  ",
+                    Color::DarkGray,
+                    &value.after_pretty(),
+                    value.after_style(),
+                );
+            }
+        }
+
+        lines
+    }
+
+    fn field(label: &str, value: &str) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(
+                format!("{:<12}", label),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::from(value.to_string()),
+        ])
+    }
+
+    /// Adds a value over as many lines as it was pretty-printed to, marking where it starts
+    fn push_value(
+        lines: &mut Vec<Line<'static>>,
+        marker: &'static str,
+        marker_color: Color,
+        value: &str,
+        style: Style,
+    ) {
+        for (i, line) in value.lines().enumerate() {
+            let marker = if i == 0 { marker } else { "  " };
+            lines.push(Line::from(vec![
+                Span::styled(marker, Style::default().fg(marker_color)),
+                Span::styled(line.to_string(), style),
+            ]));
+        }
+    }
+
+    /// Formats a path to an attribute from a plan, e.g. `["ingress", 0, "cidr"]` as `ingress[0].cidr`
+    fn attribute_path(path: &[Value]) -> String {
+        let mut out = String::new();
+        for step in path {
+            match step {
+                Value::String(name) if out.is_empty() => out.push_str(name),
+                Value::String(name) => out.push_str(&format!(".{}", name)),
+                other => out.push_str(&format!("[{}]", other)),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use serde_json::json;
+
+    use crate::model::{tf_plan::TfPlan, trowel_diff::TrowelDiff};
+
+    use super::*;
+
+    #[test]
+    fn test_wrapped_value_can_be_scrolled_to_its_end() {
+        let mut change = TfPlan::test_change("aws_iam_policy.admin", "create");
+        change["change"]["before"] = json!(null);
+        change["change"]["after"] = json!({"policy": format!("{}END", "x".repeat(300))});
+        let mut state = TreeViewState::new(TrowelDiff::test(vec![change]));
+        state.select_resource("aws_iam_policy.admin");
+
+        let area = Rect::new(0, 0, 24, 12);
+        let mut buf = Buffer::empty(area);
+        let keymap = Keymap::default();
+        DetailView::new(&keymap).render(area, &mut buf, &mut state);
+        state.detail_scroll = usize::MAX;
+        DetailView::new(&keymap).render(area, &mut buf, &mut state);
+
+        // The last row is at the bottom of the pane rather than beyond it
+        let row = |y: u16| -> String {
+            (1..area.width - 1)
+                .map(|x| buf[(x, y)].symbol().to_string())
+                .collect()
+        };
+        assert!(row(area.height - 2).contains("END"));
+        assert!(row(area.height - 3).starts_with('x'));
+    }
+
+    #[test]
+    fn test_attribute_path() {
+        let path = vec![json!("ingress"), json!(0), json!("cidr")];
+        assert_eq!(DetailView::attribute_path(&path), "ingress[0].cidr");
+    }
+}
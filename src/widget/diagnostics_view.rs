@@ -0,0 +1,224 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::tf_event::{TfEventDiagnostic, TfEventDiagnosticSnippet},
+    state::diagnostics_view_state::DiagnosticsViewState,
+};
+
+pub struct DiagnosticsView<'a> {
+    keymap: &'a Keymap,
+}
+
+impl StatefulWidget for DiagnosticsView<'_> {
+    type State = DiagnosticsViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let list_height =
+            (state.diagnostics.len() as u16 + Self::BLOCK_HEIGHT).min(area.height / 3);
+        let [list_area, detail_area] =
+            Layout::vertical([Constraint::Length(list_height), Constraint::Min(1)]).areas(area);
+
+        let items: Vec<ListItem> = state
+            .diagnostics
+            .iter()
+            .map(|d| ListItem::new(Self::heading(d)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(Self::title()))
+            .highlight_style(
+                Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightBlue)
+                    .add_modifier(Modifier::BOLD),
+            );
+        StatefulWidget::render(list, list_area, buf, &mut state.list_state);
+
+        let hint = self.keymap.hint(
+            KeyContext::Diagnostics,
+            &[(
+                &[Action::ScrollDetailDown, Action::ScrollDetailUp],
+                "scroll",
+            )],
+        );
+        let block =
+            Block::bordered().title_bottom(Line::from(format!(" {} ", hint)).right_aligned());
+        // Crash output and long details wrap, so scrolling stops once the last wrapped row is
+        // at the bottom
+        let inner = block.inner(detail_area);
+        let detail = state.selected().map(Self::detail).unwrap_or_default();
+        let paragraph = Paragraph::new(detail).wrap(Wrap { trim: false });
+        let rows = paragraph.line_count(inner.width);
+        let detail_scroll = state
+            .detail_scroll
+            .min(rows.saturating_sub(usize::from(inner.height)));
+        let scroll = u16::try_from(detail_scroll).unwrap_or(u16::MAX);
+        paragraph
+            .block(block)
+            .scroll((scroll, 0))
+            .render(detail_area, buf);
+        state.detail_scroll = detail_scroll;
+    }
+}
+
+impl<'a> DiagnosticsView<'a> {
+    const BLOCK_HEIGHT: u16 = 2;
+
+    pub fn new(keymap: &'a Keymap) -> Self {
+        Self { keymap }
+    }
+
+    fn title() -> Span<'static> {
+        Span::styled(" Plan failed ", Style::default().fg(Color::Red)).add_modifier(Modifier::BOLD)
+    }
+
+    fn severity_color(diagnostic: &TfEventDiagnostic) -> Color {
+        match diagnostic.severity.as_str() {
+            "error" => Color::Red,
+            "warning" => Color::Yellow,
+            _ => Color::Gray,
+        }
+    }
+
+    fn heading(diagnostic: &TfEventDiagnostic) -> Line<'_> {
+        let mut spans = vec![
+            Span::styled(
+                format!("{}: ", diagnostic.severity),
+                Style::default()
+                    .fg(Self::severity_color(diagnostic))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::from(diagnostic.summary.as_str()),
+        ];
+        if let Some(range) = &diagnostic.range {
+            spans.push(Span::styled(
+                format!(" ({}:{})", range.filename, range.start.line),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn detail(diagnostic: &TfEventDiagnostic) -> Vec<Line<'_>> {
+        let mut lines = vec![Self::heading(diagnostic), Line::from("")];
+
+        if let Some(address) = &diagnostic.address {
+            lines.push(Line::from(vec![
+                Span::styled("Address: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::from(address.as_str()),
+            ]));
+        }
+        if let Some(range) = &diagnostic.range {
+            lines.push(Line::from(vec![
+                Span::styled("Location: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::from(format!(
+                    "{} line {}, column {} to line {}, column {}",
+                    range.filename,
+                    range.start.line,
+                    range.start.column,
+                    range.end.line,
+                    range.end.column
+                )),
+            ]));
+        }
+        if let Some(snippet) = &diagnostic.snippet {
+            lines.push(Line::from(""));
+            lines.extend(Self::snippet(snippet, Self::severity_color(diagnostic)));
+        }
+        if !diagnostic.detail.is_empty() {
+            lines.push(Line::from(""));
+            lines.extend(diagnostic.detail.lines().map(Line::from));
+        }
+
+        lines
+    }
+
+    fn snippet(snippet: &TfEventDiagnosticSnippet, highlight: Color) -> Vec<Line<'_>> {
+        let gutter = Style::default().fg(Color::DarkGray);
+        let highlight = Style::default()
+            .fg(highlight)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        let mut lines = Vec::new();
+
+        if let Some(context) = &snippet.context {
+            lines.push(Line::from(Span::styled(
+                format!("  on {}:", context),
+                Style::default().add_modifier(Modifier::ITALIC),
+            )));
+        }
+
+        // Highlight offsets are byte offsets into the whole snippet, so track where each line starts
+        let mut offset = 0;
+        for (i, code) in snippet.code.split('\n').enumerate() {
+            let start = snippet.highlight_start_offset.saturating_sub(offset);
+            let end = snippet.highlight_end_offset.saturating_sub(offset);
+            let mut spans = vec![Span::styled(
+                format!("{:>5} │ ", snippet.start_line + i),
+                gutter,
+            )];
+            match (
+                code.get(..start),
+                code.get(start..end.min(code.len())),
+                code.get(end..),
+            ) {
+                (Some(before), Some(within), after) if !within.is_empty() => {
+                    spans.push(Span::from(before));
+                    spans.push(Span::styled(within, highlight));
+                    spans.push(Span::from(after.unwrap_or_default()));
+                }
+                _ => spans.push(Span::from(code)),
+            }
+            lines.push(Line::from(spans));
+            offset += code.len() + 1;
+        }
+
+        for value in &snippet.values {
+            lines.push(Line::from(vec![
+                Span::styled("      ├ ", gutter),
+                Span::styled(
+                    value.traversal.as_str(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::from(format!(" {}", value.statement)),
+            ]));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        buffer::Buffer,
+        crossterm::event::{Event, KeyCode, KeyEvent},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_wrapped_crash_output_can_be_scrolled_to_its_end() {
+        let stderr = format!("panic: {}\nEND", "goroutine ".repeat(40));
+        let mut state = DiagnosticsViewState::new(vec![TfEventDiagnostic::from_stderr(stderr)]);
+        let keymap = Keymap::default();
+        let area = Rect::new(0, 0, 30, 15);
+        let mut buf = Buffer::empty(area);
+        for _ in 0..100 {
+            state.process_event(Event::Key(KeyEvent::from(KeyCode::PageDown)), &keymap);
+        }
+        DiagnosticsView::new(&keymap).render(area, &mut buf, &mut state);
+
+        // The last row sits at the bottom of the detail rather than beyond it
+        let last_row: String = (1..area.width - 1)
+            .map(|x| buf[(x, area.height - 2)].symbol().to_string())
+            .collect();
+        assert_eq!(last_row.trim_end(), "END");
+        assert!(state.detail_scroll > 0);
+    }
+}
@@ -34,7 +34,7 @@ impl ErrorView {
         Block::bordered().title(Self::title())
     }
 
-    fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
+    pub fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
         let [area] = Layout::horizontal([horizontal])
             .flex(Flex::Center)
             .areas(area);
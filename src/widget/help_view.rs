@@ -0,0 +1,102 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    state::help_view_state::HelpViewState,
+};
+
+use super::error_view::ErrorView;
+
+/// The keys that can be pressed in a view, shown over it
+pub struct HelpView {
+    context: KeyContext,
+    help: Vec<(String, &'static str)>,
+    hint: String,
+    scroll_hint: String,
+}
+
+impl StatefulWidget for HelpView {
+    type State = HelpViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let key_width = self.help.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        let description_width = self.help.iter().map(|(_, d)| d.len()).max().unwrap_or(0);
+        let lines: Vec<_> = self
+            .help
+            .iter()
+            .map(|(keys, description)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", keys, width = key_width),
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::from(*description),
+                ])
+            })
+            .collect();
+
+        // The help is scrolled when it's taller than the view it's shown over
+        let area = ErrorView::center(
+            area,
+            Constraint::Length((key_width + description_width) as u16 + 6),
+            Constraint::Length(
+                u16::try_from(self.help.len() + 2)
+                    .unwrap_or(u16::MAX)
+                    .min(area.height),
+            ),
+        );
+        state.page_rows = usize::from(area.height.saturating_sub(2));
+        state.scroll = state
+            .scroll
+            .min(self.help.len().saturating_sub(state.page_rows));
+        let hint = if self.help.len() > state.page_rows {
+            &self.scroll_hint
+        } else {
+            &self.hint
+        };
+
+        let block = Block::bordered()
+            .border_type(BorderType::Thick)
+            .style(Color::Blue)
+            .title(Self::title(self.context))
+            .title_bottom(Line::from(format!(" {} ", hint)).right_aligned());
+        let popup = Paragraph::new(lines)
+            .style(Style::default().fg(Color::Reset))
+            .block(block)
+            .scroll((u16::try_from(state.scroll).unwrap_or(u16::MAX), 0));
+        Clear.render(area, buf);
+        popup.render(area, buf);
+    }
+}
+
+impl HelpView {
+    /// The help for a view, including the keys for switching views when it's one of several
+    pub fn new(keymap: &Keymap, context: KeyContext, switches_views: bool) -> Self {
+        let close = (&[Action::ToggleHelp][..], "close");
+        Self {
+            context,
+            help: keymap.help(context, switches_views),
+            hint: keymap.hint(KeyContext::Help, &[close]),
+            scroll_hint: keymap.hint(
+                KeyContext::Help,
+                &[close, (&[Action::Down, Action::Up], "scroll")],
+            ),
+        }
+    }
+
+    fn title(context: KeyContext) -> Span<'static> {
+        Span::styled(
+            format!(" Keys: {} ", context.name()),
+            Style::default().fg(Color::Blue),
+        )
+        .add_modifier(Modifier::BOLD)
+    }
+}
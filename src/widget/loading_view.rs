@@ -0,0 +1,59 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::state::loading_view_state::LoadingViewState;
+
+pub struct LoadingView {}
+
+impl StatefulWidget for LoadingView {
+    type State = LoadingViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let lines = vec![
+            Self::task_line("Reading plan as JSON", state.diff_loaded, state.ticks),
+            Self::task_line(
+                "Rendering plan as text",
+                state.text_plan_loaded,
+                state.ticks,
+            ),
+        ];
+        let block = Block::bordered().title(Self::title());
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+impl LoadingView {
+    const SPINNER: [&'static str; 4] = ["|", "/", "-", "\\"];
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn title() -> Span<'static> {
+        Span::styled(" Loading plan... ", Style::default().fg(Color::Yellow))
+            .add_modifier(Modifier::BOLD)
+    }
+
+    fn task_line(label: &str, done: bool, ticks: usize) -> Line<'_> {
+        let marker = if done {
+            Span::styled("done", Style::default().fg(Color::Green))
+        } else {
+            let frame = Self::SPINNER
+                .get(ticks % Self::SPINNER.len())
+                .unwrap_or(&" ");
+            Span::styled(*frame, Style::default().fg(Color::Yellow))
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("{}: ", label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            marker,
+        ])
+    }
+}
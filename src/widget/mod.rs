@@ -1,5 +1,12 @@
 pub mod app_view;
+pub mod delta_view;
+pub mod detail_view;
+pub mod diagnostics_view;
 pub mod error_view;
+pub mod help_view;
+pub mod loading_view;
 pub mod planning_view;
+pub mod state_view;
 pub mod text_view;
 pub mod tree_view;
+pub mod workspace_view;
@@ -2,14 +2,24 @@ use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Paragraph, StatefulWidget, Widget},
+    widgets::{Block, Paragraph, StatefulWidget, Widget, Wrap},
 };
 
-use crate::state::planning_view_state::PlanningViewState;
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::{
+        tf_event::{TfEventChange, TfEventChangeSummary, TfEventDiagnostic},
+        tf_version::TfVersion,
+        verb::Verb,
+    },
+    state::planning_view_state::{PlanningStatus, PlanningViewState},
+};
 
-pub struct PlanningView {}
+pub struct PlanningView<'a> {
+    keymap: &'a Keymap,
+}
 
-impl StatefulWidget for PlanningView {
+impl StatefulWidget for PlanningView<'_> {
     type State = PlanningViewState;
 
     fn render(
@@ -18,36 +28,219 @@ impl StatefulWidget for PlanningView {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
-        let lines: Vec<Line<'_>> = state
-            .plan_stdout
-            .iter()
-            .map(|l| Span::styled(l, Style::default().fg(Color::Gray)))
-            .map(Line::from)
-            .collect();
-        let block = Block::bordered().title(Self::title());
-        let line_count = lines.len();
-        let paragraph = Paragraph::new(lines)
+        let version = state.tf_version.as_ref();
+        let mut lines = if matches!(state.status, PlanningStatus::Initializing) {
+            state
+                .init_output
+                .iter()
+                .map(|m| Self::init_output_line(m))
+                .collect()
+        } else {
+            Self::stat_lines(state)
+        };
+        if let Some(summary) = &state.change_summary {
+            let show_forget = version.is_none_or(TfVersion::supports_forget);
+            lines.push(Self::summary_line(summary, show_forget));
+        }
+        for d in &state.diagnostics {
+            lines.push(Line::from(""));
+            lines.extend(Self::diagnostic_lines(d));
+        }
+        if matches!(state.status, PlanningStatus::Interrupted) {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Planned before the interrupt:",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            lines.extend(state.planned_changes.iter().map(Self::planned_change_line));
+        }
+
+        let mut block = Block::bordered().title(self.title(&state.status));
+        if let Some(version) = version {
+            block = block.title(Line::from(format!(" {} ", version)).right_aligned());
+        }
+        // Long lines wrap, so the newest output is kept in sight by counting wrapped rows
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+        let rows = paragraph.line_count(block.inner(area).width);
+        paragraph
             .block(block)
-            .scroll((Self::scroll_y(line_count, &area), 0));
-        paragraph.render(area, buf);
+            .scroll((Self::scroll_y(rows, &area), 0))
+            .render(area, buf);
     }
 }
 
-impl PlanningView {
+impl<'a> PlanningView<'a> {
     const BLOCK_HEIGHT: u16 = 2;
 
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(keymap: &'a Keymap) -> Self {
+        Self { keymap }
+    }
+
+    fn title(&self, status: &PlanningStatus) -> Span<'static> {
+        let quit = (Action::Quit, "quit");
+        let (text, keys, color) = match status {
+            PlanningStatus::Running => {
+                ("Planning...", vec![(Action::Quit, "cancel")], Color::Yellow)
+            }
+            PlanningStatus::Interrupting => ("Interrupting...", vec![], Color::Red),
+            PlanningStatus::Interrupted => ("Plan interrupted", vec![quit], Color::Red),
+            PlanningStatus::InitRequired => (
+                "Working directory is not initialized",
+                vec![(Action::RunInit, "run init"), quit],
+                Color::Red,
+            ),
+            PlanningStatus::Initializing => (
+                "Initializing...",
+                vec![(Action::Quit, "cancel")],
+                Color::Yellow,
+            ),
+        };
+        let text = format!(" {}{} ", text, self.key_note(&keys));
+        Span::styled(text, Style::default().fg(color)).add_modifier(Modifier::BOLD)
+    }
+
+    /// The keys for some actions, e.g. ` (i to run init, q to quit)`, leaving out unbound actions
+    fn key_note(&self, items: &[(Action, &str)]) -> String {
+        let parts: Vec<_> = items
+            .iter()
+            .filter_map(|(action, label)| {
+                let key = self.keymap.first_key(KeyContext::Planning, *action)?;
+                Some(format!("{} to {}", key, label))
+            })
+            .collect();
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", parts.join(", "))
+        }
+    }
+
+    fn stat_lines(state: &PlanningViewState) -> Vec<Line<'_>> {
+        let version = state.tf_version.as_ref();
+        let mut lines = vec![
+            Self::stat_line("Refreshed", format!("{} resources", state.refreshed_count)),
+            Self::stat_line(
+                "Refreshing",
+                state
+                    .currently_refreshing()
+                    .cloned()
+                    .unwrap_or("-".to_string()),
+            ),
+            Self::stat_line("Planned changes", state.planned_changes.len().to_string()),
+        ];
+        if version.is_some_and(TfVersion::supports_ephemeral_resources) {
+            lines.push(Self::stat_line(
+                "Ephemeral resources opened",
+                state.ephemeral_opened_count.to_string(),
+            ));
+        }
+        if let Some(message) = &state.last_message {
+            lines.push(Self::stat_line("Last message", message.clone()));
+        }
+        lines
+    }
+
+    fn init_output_line(message: &str) -> Line<'_> {
+        Line::from(Span::styled(message, Style::default().fg(Color::Gray)))
     }
 
-    fn title() -> Span<'static> {
-        Span::styled(" Initializing... ", Style::default().fg(Color::Yellow))
-            .add_modifier(Modifier::BOLD)
+    fn planned_change_line(change: &TfEventChange) -> Line<'_> {
+        let verb = Verb::from_event_action(&change.action);
+        let color = verb.as_ref().map(Verb::to_color).unwrap_or(Color::Gray);
+        let action = verb
+            .as_ref()
+            .map(Verb::to_past_tense)
+            .unwrap_or(change.action.clone());
+        Line::from(vec![
+            Span::styled(
+                change.resource.addr.as_str(),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::from(format!(" will be {}", action)),
+        ])
     }
 
-    fn scroll_y(line_count: usize, area: &Rect) -> u16 {
-        let line_count = line_count as u16;
+    fn stat_line(label: &str, value: String) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(
+                format!("{}: ", label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(value, Style::default().fg(Color::Gray)),
+        ])
+    }
+
+    fn summary_line(summary: &TfEventChangeSummary, show_forget: bool) -> Line<'static> {
+        let mut text = format!(
+            "Plan: {} to add, {} to change, {} to destroy",
+            summary.add, summary.change, summary.remove
+        );
+        if let (true, Some(forget)) = (show_forget, summary.forget) {
+            text.push_str(&format!(", {} to forget", forget));
+        }
+        text.push('.');
+        Line::from(Span::styled(
+            text,
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+    }
+
+    fn diagnostic_lines(diagnostic: &TfEventDiagnostic) -> Vec<Line<'_>> {
+        let color = match diagnostic.severity.as_str() {
+            "error" => Color::Red,
+            "warning" => Color::Yellow,
+            _ => Color::Gray,
+        };
+        let heading = Line::from(vec![
+            Span::styled(
+                format!("{}: ", diagnostic.severity),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                &diagnostic.summary,
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]);
+        std::iter::once(heading)
+            .chain(
+                diagnostic
+                    .detail
+                    .lines()
+                    .map(|l| Line::from(Span::styled(l, Style::default().fg(Color::Gray)))),
+            )
+            .collect()
+    }
+
+    fn scroll_y(rows: usize, area: &Rect) -> u16 {
         let viewport_height = area.height.saturating_sub(Self::BLOCK_HEIGHT);
-        line_count.saturating_sub(viewport_height)
+        let rows = u16::try_from(rows).unwrap_or(u16::MAX);
+        rows.saturating_sub(viewport_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+
+    use super::*;
+
+    #[test]
+    fn test_newest_wrapped_output_is_shown() {
+        let (mut state, _tx, _interrupt_rx) = PlanningViewState::new(None);
+        state.status = PlanningStatus::Initializing;
+        state.init_output = (0..4)
+            .map(|i| format!("message {} {}", i, "wraps onto several rows ".repeat(4)))
+            .chain(std::iter::once("newest".to_string()))
+            .collect();
+
+        let area = Rect::new(0, 0, 30, 8);
+        let mut buf = Buffer::empty(area);
+        let keymap = Keymap::default();
+        PlanningView::new(&keymap).render(area, &mut buf, &mut state);
+
+        let last_row: String = (1..area.width - 1)
+            .map(|x| buf[(x, area.height - 2)].symbol())
+            .collect();
+        assert_eq!(last_row.trim_end(), "newest");
     }
 }
@@ -0,0 +1,104 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Scrollbar, ScrollbarOrientation, StatefulWidget, Widget},
+};
+use tui_tree_widget::Tree;
+
+use crate::{
+    keymap::{Action, KeyContext},
+    state::state_view_state::StateViewState,
+};
+
+use super::error_view::ErrorView;
+
+pub struct StateView {}
+
+impl StatefulWidget for StateView {
+    type State = StateViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if state.state.is_empty() {
+            ErrorView::new("The state is empty".to_string(), Color::Yellow).render(area, buf);
+            return;
+        }
+        let Ok(tree_items) = state.state.to_tree_items(&state.query) else {
+            ErrorView::new(
+                "Failed to parse state into tree items! This should not be possible.".to_string(),
+                Color::Red,
+            )
+            .render(area, buf);
+            return;
+        };
+        let block = Block::bordered()
+            .title(Self::title())
+            .title_bottom(Self::search_line(state, tree_items.len()));
+        if let Ok(tree) = Tree::new(&tree_items) {
+            let style = Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD);
+            let tree = tree
+                .block(block)
+                .experimental_scrollbar(Some(Self::scrollbar()))
+                .highlight_style(style);
+            StatefulWidget::render(tree, area, buf, &mut state.tree_state);
+        }
+    }
+}
+
+impl StateView {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn title() -> Span<'static> {
+        Span::styled(" State ", Style::default().fg(Color::Blue)).add_modifier(Modifier::BOLD)
+    }
+
+    fn search_line(state: &StateViewState, shown: usize) -> Line<'static> {
+        if state.editing_query {
+            Line::from(vec![
+                Span::styled(" /", Style::default().add_modifier(Modifier::BOLD)),
+                Span::from(state.query.clone()),
+                Span::styled("_ ", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            ])
+        } else if state.query.is_empty() {
+            let objects = state.keymap.with_hint(
+                &format!("{} objects", shown),
+                KeyContext::Tree,
+                &[(&[Action::Search], "search")],
+            );
+            Line::from(format!(" {} ", objects))
+        } else {
+            let clear = state
+                .keymap
+                .hint(KeyContext::Tree, &[(&[Action::ClearSearch], "clear")]);
+            Line::from(vec![
+                Span::from(format!(
+                    " {} of {} objects match ",
+                    shown,
+                    state.state.len()
+                )),
+                Span::styled(
+                    format!("/{}", state.query),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::from(if clear.is_empty() {
+                    " ".to_string()
+                } else {
+                    format!(" | {} ", clear)
+                }),
+            ])
+        }
+    }
+
+    fn scrollbar() -> Scrollbar<'static> {
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .track_symbol(None)
+            .end_symbol(None)
+    }
+}
@@ -1,52 +1,176 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Rect, Size},
+    layout::Rect,
     style::{Color, Modifier, Style, Stylize},
-    text::Span,
-    widgets::{Block, Paragraph, StatefulWidget, Widget, Wrap},
+    text::{Line, Span},
+    widgets::{
+        Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget,
+        Wrap,
+    },
 };
-use tui_scrollview::ScrollView;
 
-use crate::state::text_view_state::TextViewState;
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::{text_search::highlight, verb::Verb},
+    state::text_view_state::TextViewState,
+};
+
+use super::tree_view::SEARCH_HINTS;
 
-pub struct TextView;
+pub struct TextView<'a> {
+    keymap: &'a Keymap,
+}
 
-impl StatefulWidget for TextView {
+impl StatefulWidget for TextView<'_> {
     type State = TextViewState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let text_plan = &state.plan;
+        // The border is drawn outside the scroll view so that the search stays in sight
+        let block = Block::bordered().title(Self::title());
+        let block = match self.search_line(state) {
+            Some(line) => block.title_bottom(line.right_aligned()),
+            None => block,
+        };
+        let inner = block.inner(area);
+        block.render(area, buf);
 
-        // TODO figure out why 2 is the correct value. Also this breaks when text wraps
-        let scrollview_height = text_plan.lines().count() as u16 + 2;
+        state.wrap(inner.as_size());
+        state.clamp_offset();
+        // Only the lines in view are rendered, so that plans of any length can be scrolled to
+        // the end. Each line takes at least one row, so no more lines than rows are needed.
+        let lines = state
+            .plan
+            .lines()
+            .skip(state.top_line())
+            .take(usize::from(inner.height));
+        let hidden = u16::try_from(state.top_line_rows_hidden()).unwrap_or(u16::MAX);
+        let text = Self::text(lines, &state.query).scroll((hidden, 0));
+        text.render(
+            Rect {
+                width: state.wrap_width,
+                ..inner
+            },
+            buf,
+        );
 
-        let width = if buf.area.height < scrollview_height {
-            buf.area.width - 1
-        } else {
-            buf.area.width
-        };
-        let mut scroll_view = ScrollView::new(Size::new(width, scrollview_height));
-        self.render_widgets_into_scrollview(scroll_view.buf_mut(), text_plan);
-        scroll_view.render(area, buf, &mut state.scroll_view_state)
+        if state.height() > usize::from(inner.height) {
+            let mut scrollbar_state =
+                ScrollbarState::new(state.height().saturating_sub(usize::from(inner.height)))
+                    .position(state.offset());
+            Self::scrollbar().render(inner, buf, &mut scrollbar_state);
+        }
     }
 }
 
-impl TextView {
-    pub fn new() -> Self {
-        TextView {}
+impl<'a> TextView<'a> {
+    pub fn new(keymap: &'a Keymap) -> Self {
+        TextView { keymap }
     }
 
     fn title() -> Span<'static> {
         Span::styled(" Trowel ", Style::default().fg(Color::Blue)).add_modifier(Modifier::BOLD)
     }
 
-    fn render_widgets_into_scrollview(&self, buf: &mut Buffer, text_plan: &str) {
-        let area = buf.area;
-        self.text(text_plan).render(area, buf);
+    fn text<'b>(lines: impl Iterator<Item = &'b str>, query: &str) -> Paragraph<'static> {
+        let lines: Vec<_> = lines
+            .map(|line| {
+                let span = Span::styled(line.to_string(), Self::line_style(line));
+                Line::from(highlight(span, query))
+            })
+            .collect();
+        Paragraph::new(lines).wrap(Wrap { trim: false })
     }
 
-    fn text(&self, text: &str) -> impl Widget {
-        let block = Block::bordered().title(Self::title());
-        Paragraph::new(text).wrap(Wrap { trim: false }).block(block)
+    fn scrollbar() -> Scrollbar<'static> {
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .track_symbol(None)
+            .end_symbol(None)
+    }
+
+    /// Colours lines by the change they describe, as the CLI does when colour isn't disabled
+    fn line_style(line: &str) -> Style {
+        let line = line.trim_start();
+        let verb = if line.starts_with("-/+ ") || line.starts_with("+/- ") {
+            Some(Verb::Replace)
+        } else if line.starts_with("<= ") {
+            Some(Verb::Read)
+        } else if line.starts_with("+ ") {
+            Some(Verb::Create)
+        } else if line.starts_with("- ") {
+            Some(Verb::Destroy)
+        } else if line.starts_with("~ ") {
+            Some(Verb::Update)
+        } else {
+            None
+        };
+        match verb {
+            Some(verb) => Style::default().fg(verb.to_color()),
+            None if line.starts_with("# ") => Style::default().add_modifier(Modifier::BOLD),
+            None => Style::default(),
+        }
+    }
+
+    fn search_line(&self, state: &TextViewState) -> Option<Line<'static>> {
+        let query = Span::styled(
+            format!("/{}", state.query),
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        if state.editing_query {
+            return Some(Line::from(vec![
+                Span::from(" "),
+                query,
+                Span::styled("_ ", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            ]));
+        }
+        if state.query.is_empty() {
+            let hint = self.keymap.hint(
+                KeyContext::Text,
+                &[
+                    (&[Action::Search], "search"),
+                    (
+                        &[Action::PreviousResource, Action::NextResource],
+                        "previous/next resource",
+                    ),
+                    (&[Action::ToggleHelp], "help"),
+                ],
+            );
+            return Some(Line::from(format!(" {} ", hint)));
+        }
+        let position = match state.match_position() {
+            (_, 0) => "no matches".to_string(),
+            (Some(i), count) => format!("match {} of {}", i + 1, count),
+            (None, count) => format!("{} matches", count),
+        };
+        let position = self
+            .keymap
+            .with_hint(&position, KeyContext::Text, &SEARCH_HINTS);
+        Some(Line::from(vec![
+            Span::from(" "),
+            query,
+            Span::from(format!(" {} ", position)),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_style() {
+        let fg = |line| TextView::line_style(line).fg;
+        assert_eq!(
+            fg("  + resource \"random_pet\" \"apple\" {"),
+            Some(Color::Green)
+        );
+        assert_eq!(fg("      ~ length = 2 -> 3"), Some(Color::Yellow));
+        assert_eq!(
+            fg("-/+ resource \"random_pet\" \"banana\" {"),
+            Some(Color::Magenta)
+        );
+        assert_eq!(fg(" <= data \"aws_ami\" \"web\" {"), Some(Color::Cyan));
+        assert_eq!(fg("      - tags = {} -> null"), Some(Color::Red));
+        assert_eq!(fg("        id = \"apple\""), None);
     }
 }
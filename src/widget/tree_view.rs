@@ -2,22 +2,36 @@ use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style, Stylize},
-    text::Span,
+    text::{Line, Span},
     widgets::{Block, Scrollbar, ScrollbarOrientation, StatefulWidget},
 };
 use tui_tree_widget::Tree;
 
-use crate::{model::trowel_diff::TrowelDiff, state::tree_view_state::TreeViewState};
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    model::{trowel_diff::TrowelDiff, trowel_filter::TrowelFilter, verb::Verb},
+    state::tree_view_state::TreeViewState,
+};
 
 use super::error_view::ErrorView;
 
-pub struct TreeView {}
+/// The keys for moving between matches, shown beside a search
+pub const SEARCH_HINTS: [(&[Action], &str); 2] = [
+    (&[Action::NextMatch, Action::PreviousMatch], "next/previous"),
+    (&[Action::ClearSearch], "clear"),
+];
+
+pub struct TreeView<'a> {
+    keymap: &'a Keymap,
+}
 
-impl StatefulWidget for TreeView {
+impl StatefulWidget for TreeView<'_> {
     type State = TreeViewState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        match state.diff.to_tree_items() {
+        let footer = self.verb_filter_spans(&state.diff, &state.filter);
+        state.footer_verbs = Self::footer_verbs(&footer, area);
+        match state.diff.to_tree_items(&state.query, &state.filter) {
             Ok(tree_items) => {
                 if let Ok(t) = Tree::new(&tree_items) {
                     let style = Style::new()
@@ -25,7 +39,11 @@ impl StatefulWidget for TreeView {
                         .bg(Color::LightBlue)
                         .add_modifier(Modifier::BOLD);
                     let tree = t
-                        .block(Self::wrapper_block(&state.diff))
+                        .block(Self::wrapper_block(
+                            footer,
+                            &state.filter,
+                            self.prompt_line(state),
+                        ))
                         .experimental_scrollbar(Some(Self::scrollbar()))
                         .highlight_style(style);
                     tree.render(area, buf, &mut state.tree_state);
@@ -46,19 +64,150 @@ impl StatefulWidget for TreeView {
     }
 }
 
-impl TreeView {
-    pub fn new() -> Self {
-        TreeView {}
+impl<'a> TreeView<'a> {
+    pub fn new(keymap: &'a Keymap) -> Self {
+        TreeView { keymap }
     }
 
     fn title() -> Span<'static> {
         Span::styled(" Trowel ", Style::default().fg(Color::Blue)).add_modifier(Modifier::BOLD)
     }
 
-    fn wrapper_block(diff: &TrowelDiff) -> Block<'_> {
-        Block::bordered()
-            .title(Self::title())
-            .title_bottom(diff.verb_uses_fmt())
+    fn wrapper_block(
+        footer: Vec<(Option<Verb>, Span<'static>)>,
+        filter: &TrowelFilter,
+        search_line: Option<Line<'static>>,
+    ) -> Block<'static> {
+        let footer: Vec<_> = footer.into_iter().map(|(_, span)| span).collect();
+        let mut title = vec![Self::title()];
+        if !filter.expression.is_empty() {
+            title.push(Span::styled(
+                format!("filter: {} ", filter.expression),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        let block = Block::bordered()
+            .title(Line::from(title))
+            .title_bottom(Line::from(footer));
+        match search_line {
+            Some(line) => block.title_bottom(line.right_aligned()),
+            None => block,
+        }
+    }
+
+    /// A summary of the verbs used, with the key that filters by each verb, and the verbs that
+    /// are filtered out greyed out. Each span is paired with the verb it stands for, if any.
+    fn verb_filter_spans(
+        &self,
+        diff: &TrowelDiff,
+        filter: &TrowelFilter,
+    ) -> Vec<(Option<Verb>, Span<'static>)> {
+        let uses = diff.verb_uses();
+        let mut out = Vec::new();
+
+        for verb in TrowelFilter::VERBS.iter().filter(|v| uses.contains_key(v)) {
+            let separator = if out.is_empty() { " " } else { " | " };
+            out.push((None, Span::from(separator)));
+
+            let key = Action::toggle_verb(verb)
+                .and_then(|action| self.keymap.first_key(KeyContext::Tree, action))
+                .map(|key| format!("{}:", key))
+                .unwrap_or_default();
+            let count = uses.get(verb).copied().unwrap_or_default();
+            let style = if filter.verbs.is_empty() {
+                Style::default().fg(verb.to_color())
+            } else if filter.verbs.contains(verb) {
+                Style::default().fg(Color::Black).bg(verb.to_color())
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            out.push((
+                Some(verb.clone()),
+                Span::styled(
+                    format!("{}{} {}", key, verb.name_lower(), count),
+                    style.add_modifier(Modifier::BOLD),
+                ),
+            ));
+        }
+
+        if filter.is_active()
+            && let Some(key) = self
+                .keymap
+                .first_key(KeyContext::Tree, Action::ShowAllActions)
+        {
+            out.push((None, Span::from(format!(" | {}:show all", key))));
+        }
+        if !out.is_empty() {
+            out.push((None, Span::from(" ")));
+        }
+        out
+    }
+
+    /// Where each verb's summary is drawn in the footer, so that clicks can be mapped to verbs
+    fn footer_verbs(footer: &[(Option<Verb>, Span<'static>)], area: Rect) -> Vec<(Rect, Verb)> {
+        if area.height == 0 {
+            return Vec::new();
+        }
+        // The footer starts after the bottom left corner of the border
+        let mut x = area.x + 1;
+        let mut out = Vec::new();
+        for (verb, span) in footer {
+            let width = span.width() as u16;
+            if let Some(verb) = verb {
+                out.push((Rect::new(x, area.bottom() - 1, width, 1), verb.clone()));
+            }
+            x += width;
+        }
+        out
+    }
+
+    /// The filter or search being typed, or the current search
+    fn prompt_line(&self, state: &TreeViewState) -> Option<Line<'static>> {
+        if state.editing_filter {
+            let mut spans = vec![
+                Span::styled(" filter: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::from(state.filter_input.clone()),
+                Span::styled("_ ", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            ];
+            if let Some(error) = &state.filter_error {
+                spans.push(Span::styled(
+                    format!("{} ", error),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
+            return Some(Line::from(spans));
+        }
+        self.search_line(state)
+    }
+
+    fn search_line(&self, state: &TreeViewState) -> Option<Line<'static>> {
+        let query = Span::styled(
+            format!("/{}", state.query),
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        if state.editing_query {
+            return Some(Line::from(vec![
+                Span::from(" "),
+                query,
+                Span::styled("_ ", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            ]));
+        }
+        if state.query.is_empty() {
+            return None;
+        }
+        let position = match state.match_position() {
+            (_, 0) => "no matches".to_string(),
+            (Some(i), count) => format!("match {} of {}", i + 1, count),
+            (None, count) => format!("{} matches", count),
+        };
+        let position = self
+            .keymap
+            .with_hint(&position, KeyContext::Tree, &SEARCH_HINTS);
+        Some(Line::from(vec![
+            Span::from(" "),
+            query,
+            Span::from(format!(" {} ", position)),
+        ]))
     }
 
     fn scrollbar() -> Scrollbar<'static> {
@@ -68,3 +217,62 @@ impl TreeView {
             .end_symbol(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{keymap::KeyBinding, model::tf_plan::TfPlan};
+
+    use super::*;
+
+    #[test]
+    fn test_verb_filter_spans() {
+        let diff = TrowelDiff::test(vec![
+            TfPlan::test_change("random_pet.apple", "update"),
+            TfPlan::test_change("random_pet.banana", "delete"),
+        ]);
+        let keymap = Keymap::default();
+        let text = |filter: &TrowelFilter, keymap: &Keymap| -> String {
+            TreeView::new(keymap)
+                .verb_filter_spans(&diff, filter)
+                .iter()
+                .map(|(_, span)| span.content.as_ref())
+                .collect()
+        };
+        let mut filter = TrowelFilter::default();
+        assert_eq!(text(&filter, &keymap), " 2:destroy 1 | 4:update 1 ");
+
+        filter.toggle_verb(&Verb::Destroy);
+        assert_eq!(
+            text(&filter, &keymap),
+            " 2:destroy 1 | 4:update 1 | 0:show all "
+        );
+        let spans = TreeView::new(&keymap).verb_filter_spans(&diff, &filter);
+        assert_eq!(spans[1].0, Some(Verb::Destroy));
+        assert_eq!(spans[1].1.style.bg, Some(Color::Red));
+        assert_eq!(spans[3].1.style.fg, Some(Color::DarkGray));
+
+        // Clicks are mapped to the verb drawn where they land
+        let footer = TreeView::footer_verbs(&spans, Rect::new(0, 0, 40, 10));
+        assert_eq!(footer[0], (Rect::new(2, 9, 11, 1), Verb::Destroy));
+        assert_eq!(footer[1].1, Verb::Update);
+
+        // The keys shown are those bound in the keymap
+        let mut rebound = Keymap::default();
+        rebound
+            .rebind(
+                KeyContext::Tree,
+                &BTreeMap::from([
+                    (Action::ToggleDestroy, vec![KeyBinding::char('D')]),
+                    (Action::ShowAllActions, vec![KeyBinding::char('A')]),
+                    (Action::ToggleUpdate, Vec::new()),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            text(&filter, &rebound),
+            " D:destroy 1 | update 1 | A:show all "
+        );
+    }
+}
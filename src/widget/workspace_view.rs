@@ -0,0 +1,116 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, StatefulWidget},
+};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    state::workspace_view_state::{Workspace, WorkspacePlan, WorkspaceViewState},
+};
+
+pub struct WorkspaceView<'a> {
+    keymap: &'a Keymap,
+}
+
+impl StatefulWidget for WorkspaceView<'_> {
+    type State = WorkspaceViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let items: Vec<ListItem> = state
+            .workspaces
+            .iter()
+            .map(|w| ListItem::new(Self::workspace_line(w, state)))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(Self::title())
+                    .title_bottom(self.help()),
+            )
+            .highlight_style(
+                Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightBlue)
+                    .add_modifier(Modifier::BOLD),
+            );
+        StatefulWidget::render(list, area, buf, &mut state.list_state);
+    }
+}
+
+impl<'a> WorkspaceView<'a> {
+    pub fn new(keymap: &'a Keymap) -> Self {
+        Self { keymap }
+    }
+
+    fn title() -> Span<'static> {
+        Span::styled(" Workspaces ", Style::default().fg(Color::Blue)).add_modifier(Modifier::BOLD)
+    }
+
+    fn help(&self) -> Line<'static> {
+        let hint = self.keymap.hint(
+            KeyContext::Workspaces,
+            &[
+                (&[Action::DisplayWorkspace], "show"),
+                (&[Action::Replan], "plan"),
+                (&[Action::PlanAll], "plan all"),
+                (&[Action::ToggleWorkspaces], "back"),
+            ],
+        );
+        Line::from(format!(" {} ", hint))
+    }
+
+    fn workspace_line<'w>(workspace: &'w Workspace, state: &WorkspaceViewState) -> Line<'w> {
+        let marker = if workspace.name == state.displayed {
+            "> "
+        } else {
+            "  "
+        };
+        let mut spans = vec![
+            Span::from(marker),
+            Span::styled(
+                workspace.name.as_str(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ];
+        if workspace.name == state.current {
+            spans.push(Span::styled(
+                " (selected)",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        spans.push(Span::from(" "));
+
+        match &workspace.plan {
+            WorkspacePlan::NotPlanned => spans.push(Span::styled(
+                "not planned",
+                Style::default().fg(Color::DarkGray),
+            )),
+            WorkspacePlan::Queued => {
+                spans.push(Span::styled("queued", Style::default().fg(Color::Yellow)))
+            }
+            WorkspacePlan::Planning => spans.push(Span::styled(
+                "planning...",
+                Style::default().fg(Color::Yellow),
+            )),
+            WorkspacePlan::Planned(planned) => {
+                let uses = planned.diff.verb_uses_fmt();
+                if uses.spans.is_empty() {
+                    spans.push(Span::styled(
+                        "no changes",
+                        Style::default().fg(Color::Green),
+                    ));
+                } else {
+                    spans.extend(uses.spans);
+                }
+            }
+            WorkspacePlan::Failed(message) => spans.push(Span::styled(
+                format!("failed: {}", message),
+                Style::default().fg(Color::Red),
+            )),
+        }
+        Line::from(spans)
+    }
+}